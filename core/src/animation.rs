@@ -0,0 +1,230 @@
+use std::f32::consts::TAU;
+
+use ambient_api::{
+    core::transform::components::{rotation, translation},
+    prelude::*,
+};
+
+use crate::{
+    oscillator::Oscillator,
+    packages::{
+        tangent_rider_schema::{
+            components::{
+                autospinner, game_phase, is_spawned, mover_easing, mover_loop, mover_pause,
+                mover_speed, mover_waypoints, oscillator_angle, placed_position, placed_rotation,
+            },
+            types::{GamePhase, MoverEasing},
+        },
+        this::components::is_vanished,
+    },
+    race_elapsed,
+};
+
+/// Animates spinning, moving and swinging objects.
+///
+/// Placed objects are kinematic bodies, and their poses are computed from the race clock
+/// rather than nudged each frame. The physics engine moves them towards each new
+/// pose with a matching velocity, so vehicles resting on them are carried along, and vehicles hit
+/// by them are knocked back as hard as they were hit.
+pub fn setup() {
+    // Ghosts aren't physical, so they just spin in place to preview the object
+    query(autospinner())
+        .excludes(is_spawned())
+        .each_frame(|spinners| {
+            let dt = delta_time();
+            for (spinner_id, spin) in spinners {
+                entity::mutate_component(spinner_id, rotation(), |rot| {
+                    *rot = spin_rotation(spin, dt) * *rot;
+                });
+            }
+        });
+
+    query((placed_position(), placed_rotation()))
+        .requires(is_spawned())
+        .each_frame(|objects| {
+            // During the scoreboard, the replay takes over animating them
+            if entity::get_component(entity::synchronized_resources(), game_phase())
+                == Some(GamePhase::Scoreboard)
+            {
+                return;
+            }
+
+            // Objects hold their placed pose until the race starts, so that every run sees them
+            // in the same place at the same time
+            let time = race_elapsed().map_or(0., |elapsed| elapsed.as_secs_f32());
+            for (object_id, (origin, origin_rotation)) in objects {
+                // Vanished objects stay hidden until they come back
+                if !is_animated(object_id) || entity::has_component(object_id, is_vanished()) {
+                    continue;
+                }
+
+                let mut position = origin;
+                let mut rot = origin_rotation;
+                if let Some(path) = Path::get(object_id) {
+                    position += origin_rotation * path.offset_at(time);
+                }
+                if let Some(oscillator) = Oscillator::get(object_id) {
+                    let extent = (TAU * time / oscillator.period.max(0.01)).sin();
+                    let (offset, swing) = oscillator.swing(extent);
+                    position += rot * offset;
+                    rot *= swing;
+                }
+                if let Some(spin) = entity::get_component(object_id, autospinner()) {
+                    rot = spin_rotation(spin, time) * rot;
+                }
+
+                entity::set_component(object_id, translation(), position);
+                entity::set_component(object_id, rotation(), rot);
+            }
+        });
+}
+
+/// Returns true if the object is moved by any of the animation attributes, and so needs to be a
+/// kinematic body.
+pub fn is_animated(id: EntityId) -> bool {
+    entity::has_component(id, autospinner())
+        || entity::has_component(id, mover_waypoints())
+        || entity::has_component(id, oscillator_angle())
+}
+
+/// Returns the rotation after spinning for the given time. The spin is a constant angular
+/// velocity, with X/Y/Z being yaw/pitch/roll.
+fn spin_rotation(spin: Vec3, time: f32) -> Quat {
+    Quat::from_scaled_axis(vec3(spin.y, spin.z, spin.x) * time)
+}
+
+struct Path {
+    waypoints: Vec<Vec3>,
+    speed: f32,
+    pause: f32,
+    easing: MoverEasing,
+    looped: bool,
+}
+impl Path {
+    fn get(id: EntityId) -> Option<Self> {
+        Some(Self {
+            waypoints: entity::get_component(id, mover_waypoints())?,
+            speed: entity::get_component(id, mover_speed())?,
+            pause: entity::get_component(id, mover_pause()).unwrap_or_default(),
+            easing: entity::get_component(id, mover_easing()).unwrap_or(MoverEasing::Linear),
+            looped: entity::has_component(id, mover_loop()),
+        })
+    }
+
+    /// Returns the offset from the placed position at the given time, in the object's placed
+    /// orientation.
+    fn offset_at(&self, time: f32) -> Vec3 {
+        // The path starts and ends where the object was placed. Back-and-forth paths retrace
+        // the waypoints in reverse.
+        let mut points = vec![Vec3::ZERO];
+        points.extend_from_slice(&self.waypoints);
+        if !self.looped {
+            points.extend(self.waypoints.iter().rev().skip(1));
+        }
+        points.push(Vec3::ZERO);
+
+        let speed = self.speed.max(0.01);
+        let pause = self.pause.max(0.);
+        let cycle: f32 = points
+            .windows(2)
+            .map(|segment| segment[0].distance(segment[1]) / speed + pause)
+            .sum();
+        if cycle <= 0. {
+            return Vec3::ZERO;
+        }
+
+        let mut time = time.rem_euclid(cycle);
+        for segment in points.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            let duration = from.distance(to) / speed;
+            if time < duration {
+                return from.lerp(to, self.ease(time / duration));
+            }
+            time -= duration;
+
+            if time < pause {
+                return to;
+            }
+            time -= pause;
+        }
+
+        Vec3::ZERO
+    }
+
+    fn ease(&self, t: f32) -> f32 {
+        match self.easing {
+            MoverEasing::Linear => t,
+            MoverEasing::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(waypoints: &[Vec3], speed: f32, pause: f32, looped: bool) -> Path {
+        Path {
+            waypoints: waypoints.to_vec(),
+            speed,
+            pause,
+            easing: MoverEasing::Linear,
+            looped,
+        }
+    }
+
+    fn assert_offset(path: &Path, time: f32, expected: Vec3) {
+        let offset = path.offset_at(time);
+        assert!(
+            offset.abs_diff_eq(expected, 1e-4),
+            "at {time}: {offset} != {expected}"
+        );
+    }
+
+    #[test]
+    fn back_and_forth_path() {
+        let path = path(&[vec3(10., 0., 0.)], 5., 0., false);
+        assert_offset(&path, 0., Vec3::ZERO);
+        assert_offset(&path, 1., vec3(5., 0., 0.));
+        assert_offset(&path, 2., vec3(10., 0., 0.));
+        assert_offset(&path, 3., vec3(5., 0., 0.));
+        assert_offset(&path, 4., Vec3::ZERO);
+        assert_offset(&path, 5., vec3(5., 0., 0.));
+    }
+
+    #[test]
+    fn path_pauses_at_each_waypoint() {
+        let path = path(&[vec3(10., 0., 0.)], 5., 1., false);
+        assert_offset(&path, 2.5, vec3(10., 0., 0.));
+        assert_offset(&path, 3.5, vec3(7.5, 0., 0.));
+        assert_offset(&path, 5.5, Vec3::ZERO);
+        assert_offset(&path, 6., Vec3::ZERO);
+    }
+
+    #[test]
+    fn looped_path_returns_directly() {
+        let path = path(&[vec3(10., 0., 0.), vec3(10., 10., 0.)], 10., 0., true);
+        assert_offset(&path, 1.5, vec3(10., 5., 0.));
+        // The way back is the diagonal from the last waypoint
+        assert_offset(&path, 2. + 2f32.sqrt() / 2., vec3(5., 5., 0.));
+    }
+
+    #[test]
+    fn path_without_waypoints_stays_put() {
+        assert_offset(&path(&[], 5., 0., false), 1., Vec3::ZERO);
+        assert_offset(&path(&[], 5., 1., true), 0.5, Vec3::ZERO);
+    }
+
+    #[test]
+    fn ease_in_out() {
+        let path = Path {
+            easing: MoverEasing::EaseInOut,
+            ..path(&[], 1., 0., false)
+        };
+        assert_eq!(path.ease(0.), 0.);
+        assert_eq!(path.ease(0.5), 0.5);
+        assert_eq!(path.ease(1.), 1.);
+        assert!(path.ease(0.25) < 0.25);
+        assert!(path.ease(0.75) > 0.75);
+    }
+}
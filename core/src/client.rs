@@ -3,37 +3,78 @@ use std::f32::consts::PI;
 use ambient_api::{
     core::{
         camera::{
-            components::{fog, fovy},
+            components::fog,
             concepts::{
                 PerspectiveInfiniteReverseCamera, PerspectiveInfiniteReverseCameraOptional,
             },
         },
         messages::Frame,
-        physics::components::linear_velocity,
         player::components::user_id,
+        rendering::components::color,
+        text::components::font_size,
         transform::components::{local_to_world, lookat_target, lookat_up, rotation, translation},
         ui::components::focusable,
     },
-    element::{use_entity_component, use_query},
-    input::{Input, InputDelta},
+    ecs::GeneralQuery,
+    element::{use_entity_component, use_frame, use_query, use_state},
+    input::{GamepadButton, Input, InputDelta},
     prelude::*,
 };
 use packages::{
     tangent_rider_schema::{
         components::{
-            active_players, game_phase, player_construction_mode, player_current_spawnable_ghost,
-            player_deaths, player_is_ready, player_money, start_position, winner,
+            active_players, game_phase, level_record_holder, level_record_time, play_start_time,
+            player_construction_mode, player_current_spawnable_ghost, player_deaths,
+            player_finish_time, player_income_amounts, player_income_sources, player_is_ready,
+            player_money, player_new_level_record, player_new_personal_best, player_placed_parts,
+            player_team, player_vehicle_def, server_time, spawnable_cost, spawnable_name,
+            start_position, team_color, team_members, team_mode, team_money, team_name, winner,
+            winning_team,
         },
         concepts::Spawnable,
         types::{ConstructionMode, GamePhase},
     },
-    tangent_schema::player::components as pc,
-    this::messages::{
-        ConstructionCancel, ConstructionRotateGhost, ConstructionSetGhostPosition,
-        ConstructionSetMode, ConstructionSpawn, ConstructionSpawnGhost, MarkAsReady,
+    tangent_schema::{
+        player::components as pc,
+        vehicle::def::components::{self as def, is_def},
+    },
+    this::{
+        components::{gamepad_selected_spawnable, minimap_camera_target, storage_error},
+        messages::{
+            ConstructionCancel, ConstructionRotateGhost, ConstructionSetGhostPosition,
+            ConstructionSetMode, ConstructionSpawn, ConstructionSpawnGhost, MarkAsReady,
+            SelectVehicle, ToggleTeamMode,
+        },
     },
 };
 
+mod controls;
+mod gamepad;
+mod hud;
+mod minimap;
+mod oscillator;
+mod replay_viewer;
+mod round_log;
+mod spectator;
+mod swing_preview;
+mod track;
+mod vehicle_camera;
+
+use controls::{use_controls, Action, Controls, ControlsPanel, Rebinder};
+use gamepad::GamepadInput;
+use hud::Hud;
+use minimap::Minimap;
+use replay_viewer::{ReplayControls, ReplayViewer};
+use round_log::{KillFeed, RoundLog};
+use spectator::{Spectator, SpectatorLabel};
+use swing_preview::SwingPreviews;
+use vehicle_camera::VehicleCamera;
+
+/// How fast a gamepad's right stick turns the camera, in radians per second.
+const GAMEPAD_LOOK_SPEED: f32 = 2.5;
+/// How fast a gamepad's right stick rotates the construction ghost, in radians per second.
+const GAMEPAD_ROTATE_SPEED: f32 = PI / 2.;
+
 #[main]
 pub async fn main() {
     let camera_id = PerspectiveInfiniteReverseCamera {
@@ -52,12 +93,18 @@ pub async fn main() {
     .with(lookat_up(), Vec3::Z)
     .spawn();
 
+    controls::setup();
+    round_log::setup();
+
     entity::wait_for_component(entity::synchronized_resources(), start_position())
         .await
         .unwrap();
 
     let mut phase = Phase::Construction(Default::default());
+    let mut rebinder = Rebinder::default();
     Frame::subscribe(move |_| {
+        rebinder.tick();
+
         let Some(game_phase) =
             entity::get_component(entity::synchronized_resources(), game_phase())
         else {
@@ -67,19 +114,20 @@ pub async fn main() {
         phase.tick(game_phase, camera_id);
     });
 
-    GameUI.el().spawn_interactive();
+    GameUI::el(camera_id).spawn_interactive();
+    SwingPreviews.el().spawn_interactive();
 }
 
 #[element_component]
-fn GameUI(hooks: &mut Hooks) -> Element {
+fn GameUI(hooks: &mut Hooks, camera_id: EntityId) -> Element {
     let Some(phase) = use_entity_component(hooks, entity::synchronized_resources(), game_phase())
     else {
         return Element::new();
     };
 
     match phase {
-        GamePhase::Construction => ConstructionUI.el(),
-        GamePhase::Play => PlayUI.el(),
+        GamePhase::Construction => ConstructionUI::el(camera_id),
+        GamePhase::Play => PlayUI::el(camera_id),
         GamePhase::Scoreboard => ScoreboardUI.el(),
     }
 }
@@ -87,28 +135,28 @@ fn GameUI(hooks: &mut Hooks) -> Element {
 enum Phase {
     Construction(Construction),
     Play(Play),
-    Scoreboard,
+    Scoreboard(ReplayViewer),
 }
 impl Phase {
     pub fn tick(&mut self, game_phase: GamePhase, camera_id: EntityId) {
         let running_phase = match self {
             Phase::Construction(_) => GamePhase::Construction,
             Phase::Play(_) => GamePhase::Play,
-            Phase::Scoreboard => GamePhase::Scoreboard,
+            Phase::Scoreboard(_) => GamePhase::Scoreboard,
         };
 
         if game_phase != running_phase {
             *self = match game_phase {
                 GamePhase::Construction => Phase::Construction(Default::default()),
                 GamePhase::Play => Phase::Play(Default::default()),
-                GamePhase::Scoreboard => Phase::Scoreboard,
+                GamePhase::Scoreboard => Phase::Scoreboard(Default::default()),
             }
         }
 
         match self {
             Phase::Construction(p) => p.tick(camera_id),
             Phase::Play(p) => p.tick(camera_id),
-            Phase::Scoreboard => {}
+            Phase::Scoreboard(p) => p.tick(camera_id),
         }
     }
 }
@@ -133,23 +181,35 @@ impl Default for FlyCamera {
     }
 }
 impl FlyCamera {
+    /// Moves and turns the camera with analog input, such as a gamepad's sticks.
+    fn steer(&mut self, movement: Vec2, look: Vec2) {
+        self.camera_yaw =
+            (self.camera_yaw + look.x * GAMEPAD_LOOK_SPEED * delta_time()).rem_euclid(2. * PI);
+        self.camera_pitch = (self.camera_pitch - look.y * GAMEPAD_LOOK_SPEED * delta_time())
+            .clamp(-89f32.to_radians(), 89f32.to_radians());
+
+        let rot = Quat::from_rotation_z(self.camera_yaw) * Quat::from_rotation_x(self.camera_pitch);
+        self.camera_position += rot * vec3(movement.x, -movement.y, 0.) * 10. * delta_time();
+    }
+
     fn tick(&mut self, camera_id: EntityId, delta: &InputDelta, input: &Input, force_angle: bool) {
-        if input.mouse_buttons.contains(&MouseButton::Right) || force_angle {
-            self.camera_yaw =
-                (self.camera_yaw + delta.mouse_position.x * 1f32.to_radians()).rem_euclid(2. * PI);
-            self.camera_pitch = (self.camera_pitch + delta.mouse_position.y * 1f32.to_radians())
-                .clamp(-89f32.to_radians(), 89f32.to_radians());
+        let controls = Controls::get();
+        if controls.is_down(Action::Look, input) || force_angle {
+            let look = controls.look_delta(delta.mouse_position);
+            self.camera_yaw = (self.camera_yaw + look.x).rem_euclid(2. * PI);
+            self.camera_pitch =
+                (self.camera_pitch + look.y).clamp(-89f32.to_radians(), 89f32.to_radians());
         }
 
         let rot = Quat::from_rotation_z(self.camera_yaw) * Quat::from_rotation_x(self.camera_pitch);
         let movement = [
-            (KeyCode::W, -Vec3::Y),
-            (KeyCode::S, Vec3::Y),
-            (KeyCode::A, -Vec3::X),
-            (KeyCode::D, Vec3::X),
+            (Action::Forward, -Vec3::Y),
+            (Action::Back, Vec3::Y),
+            (Action::Left, -Vec3::X),
+            (Action::Right, Vec3::X),
         ]
         .iter()
-        .filter(|(key, _)| input.keys.contains(key))
+        .filter(|(action, _)| controls.is_down(*action, input))
         .fold(Vec3::ZERO, |acc, (_, dir)| acc + *dir);
         self.camera_position += rot * movement * 10. * delta_time();
 
@@ -164,15 +224,20 @@ impl FlyCamera {
 
 pub struct Construction {
     camera: FlyCamera,
+    gamepad: GamepadInput,
     last_send_time: Duration,
     mouse_delta_accumulator: Vec2,
+    /// The names and costs of the spawnables, for browsing them with the gamepad.
+    spawnables: GeneralQuery<(Component<String>, Component<u32>)>,
 }
 impl Default for Construction {
     fn default() -> Self {
         Self {
             camera: Default::default(),
+            gamepad: Default::default(),
             last_send_time: game_time(),
             mouse_delta_accumulator: Vec2::ZERO,
+            spawnables: query((spawnable_name(), spawnable_cost())).build(),
         }
     }
 }
@@ -184,7 +249,14 @@ impl Construction {
                 .contains(&player::get_local());
 
         let (delta, input) = input::get_delta();
-        self.mouse_delta_accumulator += input.mouse_delta;
+        let controls = Controls::get();
+        self.gamepad.update(&input);
+        self.mouse_delta_accumulator += controls.look_delta(input.mouse_delta);
+
+        if let Some(target) = entity::get_component(entity::resources(), minimap_camera_target()) {
+            entity::remove_component(entity::resources(), minimap_camera_target());
+            self.camera.camera_position = target.extend(self.camera.camera_position.z);
+        }
 
         let current_ghost_id =
             entity::get_component(player::get_local(), player_current_spawnable_ghost());
@@ -192,6 +264,16 @@ impl Construction {
             entity::get_component(player::get_local(), player_construction_mode())
                 .unwrap_or(ConstructionMode::Place);
 
+        // While rotating the ghost, the right stick rotates it instead of looking around
+        if current_ghost_id.is_some() && construction_mode != ConstructionMode::Place {
+            self.mouse_delta_accumulator +=
+                self.gamepad.right_stick * vec2(1., -1.) * GAMEPAD_ROTATE_SPEED * delta_time();
+            self.camera.steer(self.gamepad.left_stick, Vec2::ZERO);
+        } else {
+            self.camera
+                .steer(self.gamepad.left_stick, self.gamepad.right_stick);
+        }
+
         self.camera.tick(
             camera_id,
             &delta,
@@ -211,17 +293,19 @@ impl Construction {
             input::set_cursor_visible(true);
         }
 
-        if delta.keys_released.contains(&KeyCode::Space) {
+        self.handle_gamepad_buttons(construction_mode);
+
+        if controls.was_released(Action::Spawn, &delta) {
             ConstructionSpawn.send_server_reliable();
-        } else if delta.keys_released.contains(&KeyCode::Escape) {
+        } else if controls.was_released(Action::Cancel, &delta) {
             ConstructionCancel.send_server_reliable();
-        } else if delta.keys_released.contains(&KeyCode::Key1) {
+        } else if controls.was_released(Action::Place, &delta) {
             ConstructionSetMode::new(ConstructionMode::Place).send_server_reliable();
-        } else if delta.keys_released.contains(&KeyCode::Key2) {
+        } else if controls.was_released(Action::RotateYaw, &delta) {
             ConstructionSetMode::new(ConstructionMode::RotateYaw).send_server_reliable();
-        } else if delta.keys_released.contains(&KeyCode::Key3) {
+        } else if controls.was_released(Action::RotatePitch, &delta) {
             ConstructionSetMode::new(ConstructionMode::RotatePitch).send_server_reliable();
-        } else if delta.keys_released.contains(&KeyCode::Key4) {
+        } else if controls.was_released(Action::RotateRoll, &delta) {
             ConstructionSetMode::new(ConstructionMode::RotateRoll).send_server_reliable();
         }
 
@@ -240,25 +324,19 @@ impl Construction {
                 }
                 ConstructionMode::RotateYaw => {
                     ConstructionRotateGhost {
-                        rotation: Quat::from_rotation_z(
-                            self.mouse_delta_accumulator.x * -1f32.to_radians(),
-                        ),
+                        rotation: Quat::from_rotation_z(-self.mouse_delta_accumulator.x),
                     }
                     .send_server_unreliable();
                 }
                 ConstructionMode::RotatePitch => {
                     ConstructionRotateGhost {
-                        rotation: Quat::from_rotation_x(
-                            self.mouse_delta_accumulator.y * -1f32.to_radians(),
-                        ),
+                        rotation: Quat::from_rotation_x(-self.mouse_delta_accumulator.y),
                     }
                     .send_server_unreliable();
                 }
                 ConstructionMode::RotateRoll => {
                     ConstructionRotateGhost {
-                        rotation: Quat::from_rotation_y(
-                            self.mouse_delta_accumulator.x * 1f32.to_radians(),
-                        ),
+                        rotation: Quat::from_rotation_y(self.mouse_delta_accumulator.x),
                     }
                     .send_server_unreliable();
                 }
@@ -269,11 +347,88 @@ impl Construction {
             }
         }
     }
+
+    /// Spawns and cancels with the face buttons, cycles the construction mode with the bumpers,
+    /// and browses the spawnables with the d-pad.
+    fn handle_gamepad_buttons(&self, construction_mode: ConstructionMode) {
+        const MODES: [ConstructionMode; 4] = [
+            ConstructionMode::Place,
+            ConstructionMode::RotateYaw,
+            ConstructionMode::RotatePitch,
+            ConstructionMode::RotateRoll,
+        ];
+
+        let gamepad = &self.gamepad;
+        if gamepad.just_pressed(GamepadButton::South) {
+            ConstructionSpawn.send_server_reliable();
+        } else if gamepad.just_pressed(GamepadButton::East) {
+            ConstructionCancel.send_server_reliable();
+        }
+
+        let mode_offset = if gamepad.just_pressed(GamepadButton::LeftTrigger) {
+            Some(MODES.len() - 1)
+        } else if gamepad.just_pressed(GamepadButton::RightTrigger) {
+            Some(1)
+        } else {
+            None
+        };
+        if let Some(offset) = mode_offset {
+            let index = MODES
+                .iter()
+                .position(|mode| *mode == construction_mode)
+                .unwrap_or_default();
+            ConstructionSetMode::new(MODES[(index + offset) % MODES.len()]).send_server_reliable();
+        }
+
+        // The rest only needs the spawnables, so skip looking them up unless they're being browsed
+        let is_browsing = [
+            GamepadButton::DPadDown,
+            GamepadButton::DPadUp,
+            GamepadButton::West,
+        ]
+        .into_iter()
+        .any(|button| gamepad.just_pressed(button));
+        if !is_browsing {
+            return;
+        }
+
+        // Browse in the same order as the sidebar
+        let mut spawnables = self.spawnables.evaluate();
+        spawnables.sort_by_key(|(_, (name, cost))| (*cost, name.clone()));
+        let spawnables: Vec<EntityId> = spawnables.into_iter().map(|(id, _)| id).collect();
+        if spawnables.is_empty() {
+            return;
+        }
+        let resources = entity::resources();
+        let selected_index = entity::get_component(resources, gamepad_selected_spawnable())
+            .and_then(|selected_id| spawnables.iter().position(|id| *id == selected_id));
+        let new_index = if gamepad.just_pressed(GamepadButton::DPadDown) {
+            Some(selected_index.map_or(0, |index| (index + 1) % spawnables.len()))
+        } else if gamepad.just_pressed(GamepadButton::DPadUp) {
+            Some(selected_index.map_or(spawnables.len() - 1, |index| {
+                (index + spawnables.len() - 1) % spawnables.len()
+            }))
+        } else {
+            None
+        };
+        if let Some(index) = new_index {
+            entity::add_component(resources, gamepad_selected_spawnable(), spawnables[index]);
+        }
+
+        if gamepad.just_pressed(GamepadButton::West) {
+            if let Some(index) = selected_index {
+                ConstructionSpawnGhost {
+                    spawnable_id: spawnables[index],
+                }
+                .send_server_reliable();
+            }
+        }
+    }
 }
 
 #[element_component]
-fn ConstructionUI(hooks: &mut Hooks) -> Element {
-    WindowSized::el([ConstructionSidebar.el()])
+fn ConstructionUI(hooks: &mut Hooks, camera_id: EntityId) -> Element {
+    WindowSized::el([ConstructionSidebar.el(), Minimap::el(camera_id, true)])
         .init(translation(), vec3(0., 0., 0.5))
         .with_clickarea()
         .el()
@@ -294,9 +449,33 @@ fn ConstructionSidebar(hooks: &mut Hooks) -> Element {
     }
 
     let is_ready = use_entity_component(hooks, player::get_local(), player_is_ready()).is_some();
-    let money =
-        use_entity_component(hooks, player::get_local(), player_money()).unwrap_or_default();
-    let spawnables = use_query(hooks, Spawnable::as_query());
+    let team_id = use_entity_component(hooks, player::get_local(), player_team());
+    let team_name =
+        use_entity_component(hooks, team_id.unwrap_or(player::get_local()), team_name());
+    let team_color =
+        use_entity_component(hooks, team_id.unwrap_or(player::get_local()), team_color())
+            .unwrap_or(Vec3::ONE);
+    let player_money = use_entity_component(hooks, player::get_local(), player_money());
+    let team_money =
+        use_entity_component(hooks, team_id.unwrap_or(player::get_local()), team_money());
+    let money = if team_id.is_some() {
+        team_money
+    } else {
+        player_money
+    }
+    .unwrap_or_default();
+    let team_mode_next_game =
+        use_entity_component(hooks, entity::synchronized_resources(), team_mode()).is_some();
+    let income_sources = use_entity_component(hooks, player::get_local(), player_income_sources())
+        .unwrap_or_default();
+    let income_amounts = use_entity_component(hooks, player::get_local(), player_income_amounts())
+        .unwrap_or_default();
+    let controls = use_controls(hooks);
+    let mut spawnables = use_query(hooks, Spawnable::as_query());
+    spawnables
+        .sort_by_key(|(_, spawnable)| (spawnable.spawnable_cost, spawnable.spawnable_name.clone()));
+    let selected_spawnable_id =
+        use_entity_component(hooks, entity::resources(), gamepad_selected_spawnable());
     let mode = use_entity_component(hooks, player::get_local(), player_construction_mode())
         .map(|mode| match mode {
             ConstructionMode::Place => "Place",
@@ -305,20 +484,45 @@ fn ConstructionSidebar(hooks: &mut Hooks) -> Element {
             ConstructionMode::RotateRoll => "Rotate Roll",
         })
         .unwrap_or("None");
+    let placed_parts = use_entity_component(hooks, player::get_local(), player_placed_parts())
+        .map_or(0, |parts| parts.len());
+    let storage_error =
+        use_entity_component(hooks, entity::synchronized_resources(), storage_error());
 
     with_rect(
         FlowColumn::el([
             FlowColumn::el([
                 Text::el("Tangent Rider").header_style(),
+                if let Some(error) = storage_error {
+                    Text::el(format!("The server couldn't save. {error}"))
+                        .with(color(), vec4(1.0, 0.3, 0.3, 1.0))
+                } else {
+                    Element::new()
+                },
                 Text::el("Use your money to build a course from the red block to the green block."),
                 Text::el("Everyone else can build, too, so don't get cocky!"),
                 Text::el("Click on an available item to try it out."),
                 Separator::el(false),
                 Text::el(format!("Mode: {mode}")),
-                Text::el("WASD to move, right-click to look around."),
-                Text::el(
-                    "Space to spawn, 1/2/3/4 for place and rotate yaw/pitch/roll respectively.",
-                ),
+                if placed_parts > 0 {
+                    Text::el(format!(
+                        "Placing part {} of this item. Cancel to take it back for a refund.",
+                        placed_parts + 1
+                    ))
+                } else {
+                    Element::new()
+                },
+                Text::el(controls.movement_help()),
+                Text::el(format!(
+                    "{} to spawn, {}/{}/{}/{} for place and rotate yaw/pitch/roll respectively.",
+                    controls.label(Action::Spawn),
+                    controls.label(Action::Place),
+                    controls.label(Action::RotateYaw),
+                    controls.label(Action::RotatePitch),
+                    controls.label(Action::RotateRoll),
+                )),
+                Text::el("Gamepad: sticks to move and look, bumpers to change mode."),
+                Text::el("D-pad and X to pick an item, A to spawn, B to cancel."),
             ])
             .with(space_between_items(), 4.0),
             Button::new("Ready!", move |_| {
@@ -327,15 +531,57 @@ fn ConstructionSidebar(hooks: &mut Hooks) -> Element {
             .style(ButtonStyle::Primary)
             .disabled(is_ready)
             .el(),
+            Button::new(
+                format!(
+                    "Team mode next game: {}",
+                    if team_mode_next_game { "On" } else { "Off" }
+                ),
+                move |_| {
+                    ToggleTeamMode.send_server_reliable();
+                },
+            )
+            .style(ButtonStyle::Regular)
+            .el(),
+            ControlsPanel.el(),
+            VehicleSelection.el(),
             with_rect(
                 FlowColumn::el(
-                    std::iter::once(Text::el(format!("Money: ${money}"))).chain(
-                        spawnables
+                    std::iter::once(
+                        Text::el(format!("Income: ${}", income_amounts.iter().sum::<u32>()))
+                            .section_style(),
+                    )
+                    .chain(
+                        income_sources
                             .into_iter()
-                            .map(|(id, spawnable)| ConstructionSpawnable::el(id, spawnable, money)),
+                            .zip(income_amounts)
+                            .map(|(source, amount)| Text::el(format!("+${amount} {source}"))),
                     ),
                 )
                 .with_padding_even(4.0)
+                .with(space_between_items(), 4.0),
+            )
+            .with_background(vec4(0.0, 0.0, 0.0, 0.5))
+            .with(fit_horizontal(), Fit::Parent),
+            with_rect(
+                FlowColumn::el(
+                    team_name
+                        .map(|name| {
+                            Text::el(format!("Team {name} (shared wallet)"))
+                                .section_style()
+                                .with(color(), team_color.extend(1.0))
+                        })
+                        .into_iter()
+                        .chain(std::iter::once(Text::el(format!("Money: ${money}"))))
+                        .chain(spawnables.into_iter().map(|(id, spawnable)| {
+                            ConstructionSpawnable::el(
+                                id,
+                                spawnable,
+                                money,
+                                selected_spawnable_id == Some(id),
+                            )
+                        })),
+                )
+                .with_padding_even(4.0)
                 .with(space_between_items(), 6.0),
             )
             .with_background(vec4(0.0, 0.0, 0.0, 0.5))
@@ -354,6 +600,7 @@ fn ConstructionSpawnable(
     spawnable_id: EntityId,
     spawnable: Spawnable,
     player_money: u32,
+    selected: bool,
 ) -> Element {
     Button::new(
         format!(
@@ -364,20 +611,82 @@ fn ConstructionSpawnable(
             ConstructionSpawnGhost { spawnable_id }.send_server_reliable();
         },
     )
-    .style(ButtonStyle::Regular)
+    .style(if selected {
+        ButtonStyle::Primary
+    } else {
+        ButtonStyle::Regular
+    })
     .disabled(spawnable.spawnable_cost > player_money)
     .el()
 }
 
+#[element_component]
+fn VehicleSelection(hooks: &mut Hooks) -> Element {
+    let defs = use_query(hooks, (is_def(), def::name()));
+    let selected_def_id = use_entity_component(hooks, player::get_local(), player_vehicle_def());
+
+    let select_button = |label: String, def_id: EntityId, selected: bool| {
+        Button::new(label, move |_| {
+            SelectVehicle { def_id }.send_server_reliable();
+        })
+        .style(if selected {
+            ButtonStyle::Primary
+        } else {
+            ButtonStyle::Regular
+        })
+        .el()
+    };
+
+    with_rect(
+        FlowColumn::el(
+            [
+                Text::el("Vehicle").section_style(),
+                select_button(
+                    "Random".to_string(),
+                    EntityId::null(),
+                    selected_def_id.is_none(),
+                ),
+            ]
+            .into_iter()
+            .chain(defs.into_iter().map(|(def_id, (_, name))| {
+                let stat = |value: Option<f32>| {
+                    value
+                        .map(|value| format!("{value:.0}"))
+                        .unwrap_or("?".to_string())
+                };
+
+                FlowColumn::el([
+                    select_button(name, def_id, selected_def_id == Some(def_id)),
+                    Text::el(format!(
+                        "Health {} / Forward {} / Side {}",
+                        stat(entity::get_component(def_id, def::max_health())),
+                        stat(entity::get_component(def_id, def::forward_force())),
+                        stat(entity::get_component(def_id, def::side_force())),
+                    )),
+                ])
+                .with(space_between_items(), 2.0)
+            })),
+        )
+        .with_padding_even(4.0)
+        .with(space_between_items(), 6.0),
+    )
+    .with_background(vec4(0.0, 0.0, 0.0, 0.5))
+    .with(fit_horizontal(), Fit::Parent)
+}
+
 pub struct Play {
-    fly_camera: FlyCamera,
+    vehicle_camera: VehicleCamera,
+    spectator: Spectator,
+    gamepad: GamepadInput,
     last_input: Input,
     last_input_send_time: Duration,
 }
 impl Default for Play {
     fn default() -> Self {
         Self {
-            fly_camera: default(),
+            vehicle_camera: default(),
+            spectator: default(),
+            gamepad: default(),
             last_input: input::get(),
             last_input_send_time: game_time(),
         }
@@ -390,36 +699,21 @@ impl Play {
                 .unwrap_or_default();
 
         if !active_players.contains(&player::get_local()) {
-            let (delta, input) = input::get_delta();
-            return self.fly_camera.tick(camera_id, &delta, &input, true);
+            return self.spectator.tick(camera_id);
         }
 
-        let _ = self.handle_camera(camera_id);
+        match entity::get_component(player::get_local(), pc::vehicle_ref()) {
+            Some(vehicle_id) => {
+                let (delta, input) = input::get_delta();
+                let _ = self
+                    .vehicle_camera
+                    .tick(camera_id, vehicle_id, &delta, &input);
+            }
+            None => self.spectator.tick(camera_id),
+        }
         self.handle_input();
     }
 
-    fn handle_camera(&mut self, camera_id: EntityId) -> Option<()> {
-        let player_id = player::get_local();
-        let vehicle_ref = entity::get_component(player_id, pc::vehicle_ref())?;
-
-        let position = entity::get_component(vehicle_ref, translation())?;
-        let rotation = entity::get_component(vehicle_ref, rotation())?;
-        let speed = entity::get_component(vehicle_ref, linear_velocity())?.length();
-
-        let new_lookat_position = position + rotation * vec3(1.5, 5.4, 1.8);
-        let new_lookat_target = new_lookat_position + rotation * -Vec3::Y;
-
-        entity::set_component(camera_id, translation(), new_lookat_position);
-        entity::set_component(camera_id, lookat_target(), new_lookat_target);
-        entity::set_component(
-            camera_id,
-            fovy(),
-            0.9 + (speed.abs() / 300.0).clamp(0.0, 1.0),
-        );
-
-        Some(())
-    }
-
     fn handle_input(&mut self) {
         let now = game_time();
         if (now - self.last_input_send_time) < Duration::from_millis(20) {
@@ -430,29 +724,41 @@ impl Play {
             return;
         }
 
+        let controls = Controls::get();
         let input = input::get();
         let delta = input.delta(&self.last_input);
         let direction = {
             let mut direction = Vec2::ZERO;
-            if input.keys.contains(&KeyCode::W) {
+            if controls.is_down(Action::Forward, &input) {
                 direction.y += 1.;
             }
-            if input.keys.contains(&KeyCode::S) {
+            if controls.is_down(Action::Back, &input) {
                 direction.y -= 1.;
             }
-            if input.keys.contains(&KeyCode::A) {
+            if controls.is_down(Action::Left, &input) {
                 direction.x -= 1.;
             }
-            if input.keys.contains(&KeyCode::D) {
+            if controls.is_down(Action::Right, &input) {
                 direction.x += 1.;
             }
+
+            // The stick takes over from the keyboard while it's being used
+            self.gamepad.update(&input);
+            if self.gamepad.left_stick != Vec2::ZERO {
+                direction = self.gamepad.left_stick;
+            }
             direction
         };
+        let gamepad = &self.gamepad;
 
         packages::this::messages::Input {
             direction,
-            jump: input.keys.contains(&KeyCode::Space),
-            respawn: delta.keys.contains(&KeyCode::K),
+            jump: controls.is_down(Action::Jump, &input)
+                || gamepad.is_pressed(GamepadButton::South)
+                || gamepad.is_pressed(GamepadButton::RightTrigger2),
+            respawn: controls.was_pressed(Action::Respawn, &delta)
+                || gamepad.just_pressed(GamepadButton::North)
+                || gamepad.just_pressed(GamepadButton::LeftTrigger2),
         }
         .send_server_unreliable();
 
@@ -462,40 +768,200 @@ impl Play {
 }
 
 #[element_component]
-fn PlayUI(hooks: &mut Hooks) -> Element {
+fn PlayUI(hooks: &mut Hooks, camera_id: EntityId) -> Element {
     let active_players =
         use_entity_component(hooks, entity::synchronized_resources(), active_players())
             .unwrap_or_default();
 
+    let is_alive = use_entity_component(hooks, player::get_local(), pc::vehicle_ref()).is_some();
+
     if !active_players.contains(&player::get_local()) {
-        return InactivePlayer.el();
+        return Group::el([
+            InactivePlayer.el(),
+            KillFeed.el(),
+            SpectatorLabel.el(),
+            Minimap::el(camera_id, false),
+            StartCountdown.el(),
+        ]);
     }
 
-    Element::new()
+    Group::el([
+        Hud.el(),
+        KillFeed.el(),
+        Minimap::el(camera_id, false),
+        if is_alive {
+            Element::new()
+        } else {
+            SpectatorLabel.el()
+        },
+        StartCountdown.el(),
+    ])
+}
+
+#[element_component]
+fn StartCountdown(hooks: &mut Hooks) -> Element {
+    let start_time =
+        use_entity_component(hooks, entity::synchronized_resources(), play_start_time());
+    let label = use_frame_value(hooks, move || {
+        let now = entity::get_component(entity::synchronized_resources(), server_time())?;
+        let remaining = start_time?.as_secs_f32() - now.as_secs_f32();
+        if remaining > 2.0 {
+            Some("3")
+        } else if remaining > 1.0 {
+            Some("2")
+        } else if remaining > 0.0 {
+            Some("1")
+        } else if remaining > -1.0 {
+            Some("GO!")
+        } else {
+            None
+        }
+    });
+
+    let Some(label) = label else {
+        return Element::new();
+    };
+
+    WindowSized::el([
+        FlowColumn::el([Text::el(label).header_style().with(font_size(), 120.)])
+            .with(fit_horizontal(), Fit::Parent)
+            .with(fit_vertical(), Fit::Parent)
+            .with(align_horizontal(), Align::Center)
+            .with(align_vertical(), Align::Center),
+    ])
 }
 
 #[element_component]
 fn ScoreboardUI(hooks: &mut Hooks) -> Element {
     let winner_id = use_entity_component(hooks, entity::synchronized_resources(), winner());
+    let winning_team_id =
+        use_entity_component(hooks, entity::synchronized_resources(), winning_team());
     let players = use_query(hooks, (user_id(), player_deaths()));
+    let teams = use_query(hooks, (team_name(), team_color(), team_members()));
+    let record_holder = use_entity_component(
+        hooks,
+        entity::synchronized_resources(),
+        level_record_holder(),
+    );
+    let record_time =
+        use_entity_component(hooks, entity::synchronized_resources(), level_record_time());
+
+    let winner_name = winner_id
+        .and_then(|id| entity::get_component(id, user_id()))
+        .unwrap_or("Unknown".to_string());
+    let header = match winning_team_id.and_then(|id| entity::get_component(id, team_name())) {
+        Some(team_name) => format!("Team {team_name} wins, thanks to {winner_name}!"),
+        None => format!("The winner is {winner_name}!"),
+    };
 
-    WindowSized::el([with_rect(Dock::el([FlowColumn::el([
-        Text::el(format!(
-            "The winner is {}!",
-            winner_id
-                .and_then(|id| entity::get_component(id, user_id()))
-                .unwrap_or("Unknown".to_string()),
-        ))
-        .header_style(),
+    let results = if teams.is_empty() {
+        FlowColumn::el(players.into_iter().map(|(id, _)| player_result(id)))
+    } else {
         FlowColumn::el(
-            players
+            teams
                 .into_iter()
-                .map(|(_, (uid, deaths))| Text::el(format!("{uid}: {deaths} deaths"))),
-        ),
-    ])
-    .with(docking(), Docking::Fill)]))
-    .with_background(vec4(0.0, 0.0, 0.0, 0.5))])
-    .with_padding_even(20.)
+                .map(|(team_id, (name, team_color, members))| {
+                    let is_winner = winning_team_id == Some(team_id);
+                    let total_deaths: u32 = members
+                        .iter()
+                        .filter_map(|id| entity::get_component(*id, player_deaths()))
+                        .sum();
+
+                    FlowColumn::el(
+                        std::iter::once(
+                            Text::el(format!(
+                                "Team {name}{}: {total_deaths} deaths",
+                                if is_winner { " (winner)" } else { "" }
+                            ))
+                            .section_style()
+                            .with(color(), team_color.extend(1.0)),
+                        )
+                        .chain(members.into_iter().map(player_result)),
+                    )
+                    .with(space_between_items(), 4.0)
+                }),
+        )
+        .with(space_between_items(), 8.0)
+    };
+
+    let record = match (record_holder, record_time) {
+        (Some(holder), Some(time)) => Text::el(format!(
+            "Level record: {} by {holder}",
+            format_race_time(time)
+        )),
+        _ => Text::el("Nobody has finished this level yet."),
+    };
+
+    let is_ready = use_entity_component(hooks, player::get_local(), player_is_ready()).is_some();
+
+    with_rect(
+        FlowColumn::el([
+            Text::el(header).header_style(),
+            record,
+            results,
+            Separator::el(false),
+            RoundLog.el(),
+            Separator::el(false),
+            ReplayControls.el(),
+            Button::new("Continue", move |_| {
+                MarkAsReady.send_server_reliable();
+            })
+            .style(ButtonStyle::Primary)
+            .disabled(is_ready)
+            .el(),
+        ])
+        .with_padding_even(4.0)
+        .with(space_between_items(), 6.0),
+    )
+    .with_margin_even(STREET)
+    .with_background(vec4(0.0, 0.0, 0.0, 0.5))
+}
+
+/// Describes how the player did in the last play phase, highlighting any records they set.
+fn player_result(player_id: EntityId) -> Element {
+    let uid = entity::get_component(player_id, user_id()).unwrap_or_default();
+    let deaths = entity::get_component(player_id, player_deaths()).unwrap_or_default();
+    let Some(time) = entity::get_component(player_id, player_finish_time()) else {
+        return Text::el(format!("{uid}: {deaths} deaths"));
+    };
+
+    let text = format!(
+        "{uid}: {deaths} deaths, finished in {}",
+        format_race_time(time)
+    );
+    if entity::has_component(player_id, player_new_level_record()) {
+        Text::el(format!("{text} - new level record!")).with(color(), vec4(1.0, 0.8, 0.2, 1.0))
+    } else if entity::has_component(player_id, player_new_personal_best()) {
+        Text::el(format!("{text} - personal best!")).with(color(), vec4(0.4, 1.0, 0.4, 1.0))
+    } else {
+        Text::el(text)
+    }
+}
+
+/// Computes a value every frame, but only re-renders the element when the value changes.
+/// Values should be rounded to what's displayed, so that continuously changing state (like a
+/// clock or positions) doesn't re-render the UI every frame.
+fn use_frame_value<T>(hooks: &mut Hooks, compute: impl Fn() -> T + Send + Sync + 'static) -> T
+where
+    T: Clone + std::fmt::Debug + Default + PartialEq + Send + Sync + 'static,
+{
+    let (value, set_value) = use_state(hooks, T::default());
+    use_frame(hooks, {
+        let value = value.clone();
+        move |_| {
+            let new_value = compute();
+            if new_value != value {
+                set_value(new_value);
+            }
+        }
+    });
+    value
+}
+
+/// Formats a race time as minutes, seconds and hundredths of a second.
+fn format_race_time(time: Duration) -> String {
+    let seconds = time.as_secs_f32();
+    format!("{}:{:05.2}", (seconds / 60.) as u32, seconds % 60.)
 }
 
 #[element_component]
@@ -0,0 +1,588 @@
+use std::cell::{Cell, RefCell};
+
+use ambient_api::{
+    element::{use_entity_component, use_state},
+    input::{Input, InputDelta},
+    prelude::*,
+};
+
+use crate::packages::this::{
+    components::{input_settings, rebinding_action},
+    messages::{InputSettings, RequestInputSettings},
+};
+
+/// The keys that actions can be bound to.
+const BINDABLE_KEYS: [KeyCode; 50] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Space,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Return,
+    KeyCode::Back,
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::LControl,
+    KeyCode::RControl,
+    KeyCode::LAlt,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+];
+/// The mouse buttons that actions can be bound to. The left button is left out, as it's used to
+/// click on the UI.
+const BINDABLE_MOUSE_BUTTONS: [MouseButton; 2] = [MouseButton::Right, MouseButton::Middle];
+/// The range of mouse sensitivity, in degrees per pixel.
+const SENSITIVITY_RANGE: (f32, f32) = (0.1, 5.0);
+
+/// Something the player can do that can be bound to a key or mouse button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Jump,
+    Respawn,
+    Look,
+    Spawn,
+    Cancel,
+    Place,
+    RotateYaw,
+    RotatePitch,
+    RotateRoll,
+    SwitchCamera,
+    SpectatePrevious,
+    SpectateNext,
+    ToggleAutoDirector,
+}
+impl Action {
+    pub const ALL: [Action; 17] = [
+        Action::Forward,
+        Action::Back,
+        Action::Left,
+        Action::Right,
+        Action::Jump,
+        Action::Respawn,
+        Action::Look,
+        Action::Spawn,
+        Action::Cancel,
+        Action::Place,
+        Action::RotateYaw,
+        Action::RotatePitch,
+        Action::RotateRoll,
+        Action::SwitchCamera,
+        Action::SpectatePrevious,
+        Action::SpectateNext,
+        Action::ToggleAutoDirector,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Forward => "Forward",
+            Action::Back => "Back",
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Jump => "Jump",
+            Action::Respawn => "Respawn",
+            Action::Look => "Look around",
+            Action::Spawn => "Spawn",
+            Action::Cancel => "Cancel",
+            Action::Place => "Place mode",
+            Action::RotateYaw => "Rotate yaw mode",
+            Action::RotatePitch => "Rotate pitch mode",
+            Action::RotateRoll => "Rotate roll mode",
+            Action::SwitchCamera => "Switch camera",
+            Action::SpectatePrevious => "Spectate previous player",
+            Action::SpectateNext => "Spectate next player",
+            Action::ToggleAutoDirector => "Toggle auto-director",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_binding(self) -> Binding {
+        match self {
+            Action::Forward => Binding::Key(KeyCode::W),
+            Action::Back => Binding::Key(KeyCode::S),
+            Action::Left => Binding::Key(KeyCode::A),
+            Action::Right => Binding::Key(KeyCode::D),
+            Action::Jump => Binding::Key(KeyCode::Space),
+            Action::Respawn => Binding::Key(KeyCode::K),
+            Action::Look => Binding::Mouse(MouseButton::Right),
+            Action::Spawn => Binding::Key(KeyCode::Space),
+            Action::Cancel => Binding::Key(KeyCode::Escape),
+            Action::Place => Binding::Key(KeyCode::Key1),
+            Action::RotateYaw => Binding::Key(KeyCode::Key2),
+            Action::RotatePitch => Binding::Key(KeyCode::Key3),
+            Action::RotateRoll => Binding::Key(KeyCode::Key4),
+            Action::SwitchCamera => Binding::Key(KeyCode::C),
+            Action::SpectatePrevious => Binding::Key(KeyCode::Left),
+            Action::SpectateNext => Binding::Key(KeyCode::Right),
+            Action::ToggleAutoDirector => Binding::Key(KeyCode::Up),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+impl Binding {
+    pub fn is_down(self, input: &Input) -> bool {
+        match self {
+            Binding::Key(key) => input.keys.contains(&key),
+            Binding::Mouse(button) => input.mouse_buttons.contains(&button),
+        }
+    }
+
+    pub fn was_pressed(self, delta: &InputDelta) -> bool {
+        match self {
+            Binding::Key(key) => delta.keys.contains(&key),
+            Binding::Mouse(button) => delta.mouse_buttons.contains(&button),
+        }
+    }
+
+    pub fn was_released(self, delta: &InputDelta) -> bool {
+        match self {
+            Binding::Key(key) => delta.keys_released.contains(&key),
+            Binding::Mouse(button) => delta.mouse_buttons_released.contains(&button),
+        }
+    }
+
+    /// A short description of the binding, for showing to the player.
+    pub fn label(self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                // Number keys are called Key0 to Key9
+                match name.strip_prefix("Key") {
+                    Some(digit) if !digit.is_empty() => digit.to_string(),
+                    _ => name,
+                }
+            }
+            Binding::Mouse(button) => format!("{button:?} mouse"),
+        }
+    }
+
+    fn to_setting(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Mouse(button) => format!("Mouse {button:?}"),
+        }
+    }
+
+    fn from_setting(value: &str) -> Option<Self> {
+        match value.strip_prefix("Mouse ") {
+            Some(button) => BINDABLE_MOUSE_BUTTONS
+                .into_iter()
+                .find(|candidate| format!("{candidate:?}") == button)
+                .map(Binding::Mouse),
+            None => BINDABLE_KEYS
+                .into_iter()
+                .find(|candidate| format!("{candidate:?}") == value)
+                .map(Binding::Key),
+        }
+    }
+}
+
+thread_local! {
+    /// The current controls, parsed once whenever they're loaded or changed.
+    static CURRENT_CONTROLS: RefCell<Controls> = RefCell::new(Controls::default());
+    /// True while an action is waiting for a key to be bound to it.
+    static IS_REBINDING: Cell<bool> = Cell::new(false);
+    /// The key or mouse button that was just bound to an action. It's ignored until it's let go,
+    /// so that it doesn't also do whatever it was already bound to.
+    static SWALLOWED_BINDING: Cell<Option<Binding>> = Cell::new(None);
+}
+
+/// Returns true if input from the binding shouldn't reach the game, because it's being used to
+/// rebind an action.
+fn is_swallowed(binding: Binding) -> bool {
+    IS_REBINDING.with(Cell::get) || SWALLOWED_BINDING.with(Cell::get) == Some(binding)
+}
+
+/// The player's key bindings and mouse settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Controls {
+    bindings: Vec<(Action, Binding)>,
+    /// How far the camera turns per pixel of mouse movement, in degrees.
+    pub sensitivity: f32,
+    pub invert_pitch: bool,
+}
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_binding()))
+                .collect(),
+            sensitivity: 1.0,
+            invert_pitch: false,
+        }
+    }
+}
+impl Controls {
+    /// Returns the current controls, or the defaults if none have been loaded.
+    pub fn get() -> Self {
+        CURRENT_CONTROLS.with(|current| current.borrow().clone())
+    }
+
+    /// Makes these the current controls, and has the server save them for the next session.
+    pub fn save(&self) {
+        let settings = self.to_settings();
+        self.make_current(settings.clone());
+        InputSettings { settings }.send_server_reliable();
+    }
+
+    /// Updates the cached controls, and the resource that the UI watches for changes.
+    fn make_current(&self, settings: String) {
+        CURRENT_CONTROLS.with(|current| *current.borrow_mut() = self.clone());
+        entity::add_component(entity::resources(), input_settings(), settings);
+    }
+
+    pub fn binding(&self, action: Action) -> Binding {
+        self.bindings
+            .iter()
+            .find(|(bound_action, _)| *bound_action == action)
+            .map_or(action.default_binding(), |(_, binding)| *binding)
+    }
+
+    pub fn set_binding(&mut self, action: Action, binding: Binding) {
+        match self
+            .bindings
+            .iter_mut()
+            .find(|(bound_action, _)| *bound_action == action)
+        {
+            Some((_, existing)) => *existing = binding,
+            None => self.bindings.push((action, binding)),
+        }
+    }
+
+    pub fn is_down(&self, action: Action, input: &Input) -> bool {
+        let binding = self.binding(action);
+        !is_swallowed(binding) && binding.is_down(input)
+    }
+
+    pub fn was_pressed(&self, action: Action, delta: &InputDelta) -> bool {
+        let binding = self.binding(action);
+        !is_swallowed(binding) && binding.was_pressed(delta)
+    }
+
+    pub fn was_released(&self, action: Action, delta: &InputDelta) -> bool {
+        let binding = self.binding(action);
+        !is_swallowed(binding) && binding.was_released(delta)
+    }
+
+    pub fn label(&self, action: Action) -> String {
+        self.binding(action).label()
+    }
+
+    /// Converts mouse movement in pixels into a change in yaw and pitch, in radians.
+    pub fn look_delta(&self, mouse_delta: Vec2) -> Vec2 {
+        let pitch_sign = if self.invert_pitch { -1. } else { 1. };
+        mouse_delta * vec2(1., pitch_sign) * self.sensitivity.to_radians()
+    }
+
+    /// Describes how to move and look around, for help text.
+    pub fn movement_help(&self) -> String {
+        format!(
+            "{}/{}/{}/{} to move, {} to look around.",
+            self.label(Action::Forward),
+            self.label(Action::Left),
+            self.label(Action::Back),
+            self.label(Action::Right),
+            self.label(Action::Look),
+        )
+    }
+
+    fn to_settings(&self) -> String {
+        let mut settings = format!(
+            "sensitivity={}\ninvert_pitch={}\n",
+            self.sensitivity, self.invert_pitch
+        );
+        for (action, binding) in &self.bindings {
+            settings += &format!("{}={}\n", action.name(), binding.to_setting());
+        }
+        settings
+    }
+
+    /// Reads settings produced by [Self::to_settings]. Anything missing or invalid keeps its
+    /// default, so that old settings still load after actions are added.
+    fn parse(settings: &str) -> Self {
+        let mut controls = Self::default();
+        for (key, value) in settings.lines().filter_map(|line| line.split_once('=')) {
+            match key {
+                "sensitivity" => {
+                    if let Ok(sensitivity) = value.parse::<f32>() {
+                        controls.sensitivity =
+                            sensitivity.clamp(SENSITIVITY_RANGE.0, SENSITIVITY_RANGE.1);
+                    }
+                }
+                "invert_pitch" => controls.invert_pitch = value == "true",
+                _ => {
+                    if let (Some(action), Some(binding)) =
+                        (Action::from_name(key), Binding::from_setting(value))
+                    {
+                        controls.set_binding(action, binding);
+                    }
+                }
+            }
+        }
+        controls
+    }
+}
+
+/// Loads the player's saved controls from the server. Players without saved controls use the
+/// defaults.
+pub fn setup() {
+    InputSettings::subscribe(|_ctx, msg| {
+        Controls::parse(&msg.settings).make_current(msg.settings);
+    });
+    RequestInputSettings.send_server_reliable();
+}
+
+/// Returns the current controls, and re-renders the element when they change.
+pub fn use_controls(hooks: &mut Hooks) -> Controls {
+    use_entity_component(hooks, entity::resources(), input_settings());
+    Controls::get()
+}
+
+/// Waits for a key or mouse button to bind to the action that is being rebound.
+pub struct Rebinder {
+    last_input: Input,
+}
+impl Default for Rebinder {
+    fn default() -> Self {
+        Self {
+            last_input: input::get(),
+        }
+    }
+}
+impl Rebinder {
+    pub fn tick(&mut self) {
+        let input = input::get();
+        let delta = input.delta(&self.last_input);
+        self.last_input = input;
+
+        // The key that was just bound goes back to doing what it's bound to once it's let go
+        if SWALLOWED_BINDING
+            .with(Cell::get)
+            .is_some_and(|binding| !binding.is_down(&self.last_input))
+        {
+            SWALLOWED_BINDING.with(|swallowed| swallowed.set(None));
+        }
+
+        let resources = entity::resources();
+        let action = entity::get_component(resources, rebinding_action())
+            .and_then(|name| Action::from_name(&name));
+        IS_REBINDING.with(|is_rebinding| is_rebinding.set(action.is_some()));
+        let Some(action) = action else {
+            return;
+        };
+
+        let binding = BINDABLE_KEYS
+            .into_iter()
+            .map(Binding::Key)
+            .chain(BINDABLE_MOUSE_BUTTONS.into_iter().map(Binding::Mouse))
+            .find(|binding| binding.was_pressed(&delta));
+        if let Some(binding) = binding {
+            let mut controls = Controls::get();
+            controls.set_binding(action, binding);
+            controls.save();
+            entity::remove_component(resources, rebinding_action());
+            IS_REBINDING.with(|is_rebinding| is_rebinding.set(false));
+            SWALLOWED_BINDING.with(|swallowed| swallowed.set(Some(binding)));
+        }
+    }
+}
+
+#[element_component]
+pub fn ControlsPanel(hooks: &mut Hooks) -> Element {
+    let (open, set_open) = use_state(hooks, false);
+    let controls = use_controls(hooks);
+    let rebinding = use_entity_component(hooks, entity::resources(), rebinding_action());
+
+    let toggle = Button::new(if open { "Hide controls" } else { "Controls" }, move |_| {
+        set_open(!open)
+    })
+    .style(ButtonStyle::Regular)
+    .el();
+    if !open {
+        return toggle;
+    }
+
+    let sensitivity_button = |label: &str, change: f32| {
+        let controls = controls.clone();
+        Button::new(label, move |_| {
+            let mut controls = controls.clone();
+            controls.sensitivity =
+                (controls.sensitivity + change).clamp(SENSITIVITY_RANGE.0, SENSITIVITY_RANGE.1);
+            controls.save();
+        })
+        .style(ButtonStyle::Regular)
+        .el()
+    };
+
+    let bindings = Action::ALL.into_iter().map(|action| {
+        let is_rebinding = rebinding.as_deref() == Some(action.name());
+        Button::new(
+            if is_rebinding {
+                format!("{}: press a key...", action.name())
+            } else {
+                format!("{}: {}", action.name(), controls.label(action))
+            },
+            move |_| {
+                let resources = entity::resources();
+                if is_rebinding {
+                    entity::remove_component(resources, rebinding_action());
+                } else {
+                    entity::add_component(resources, rebinding_action(), action.name().to_string());
+                }
+            },
+        )
+        .style(if is_rebinding {
+            ButtonStyle::Primary
+        } else {
+            ButtonStyle::Regular
+        })
+        .el()
+    });
+
+    with_rect(
+        FlowColumn::el(
+            [
+                toggle,
+                Text::el("Click an action, then press the key to bind it to."),
+                FlowRow::el([
+                    sensitivity_button("-", -0.1),
+                    Text::el(format!("Mouse sensitivity: {:.1}", controls.sensitivity)),
+                    sensitivity_button("+", 0.1),
+                ])
+                .with(space_between_items(), 4.0),
+                Button::new(
+                    format!(
+                        "Invert pitch: {}",
+                        if controls.invert_pitch { "On" } else { "Off" }
+                    ),
+                    {
+                        let controls = controls.clone();
+                        move |_| {
+                            let mut controls = controls.clone();
+                            controls.invert_pitch = !controls.invert_pitch;
+                            controls.save();
+                        }
+                    },
+                )
+                .style(ButtonStyle::Regular)
+                .el(),
+                Button::new("Reset to defaults", move |_| {
+                    Controls::default().save();
+                })
+                .style(ButtonStyle::Regular)
+                .el(),
+            ]
+            .into_iter()
+            .chain(bindings),
+        )
+        .with_padding_even(4.0)
+        .with(space_between_items(), 4.0),
+    )
+    .with_background(vec4(0.0, 0.0, 0.0, 0.5))
+    .with(fit_horizontal(), Fit::Parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip() {
+        let mut controls = Controls {
+            sensitivity: 2.5,
+            invert_pitch: true,
+            ..Default::default()
+        };
+        controls.set_binding(Action::Jump, Binding::Key(KeyCode::Key7));
+        controls.set_binding(Action::Respawn, Binding::Mouse(MouseButton::Middle));
+
+        assert_eq!(Controls::parse(&controls.to_settings()), controls);
+    }
+
+    #[test]
+    fn parse_keeps_defaults_for_missing_or_invalid_settings() {
+        let controls = Controls::parse(
+            "sensitivity=fast\n\
+             invert_pitch=true\n\
+             Jump=NotAKey\n\
+             Respawn=Mouse Left\n\
+             Dance=J\n\
+             no equals sign\n\
+             Forward=Up\n",
+        );
+
+        let mut expected = Controls {
+            invert_pitch: true,
+            ..Default::default()
+        };
+        expected.set_binding(Action::Forward, Binding::Key(KeyCode::Up));
+        assert_eq!(controls, expected);
+    }
+
+    #[test]
+    fn parse_clamps_sensitivity() {
+        assert_eq!(
+            Controls::parse("sensitivity=100").sensitivity,
+            SENSITIVITY_RANGE.1
+        );
+        assert_eq!(
+            Controls::parse("sensitivity=-1").sensitivity,
+            SENSITIVITY_RANGE.0
+        );
+    }
+
+    #[test]
+    fn number_key_labels() {
+        assert_eq!(Binding::Key(KeyCode::Key3).label(), "3");
+        assert_eq!(Binding::Key(KeyCode::K).label(), "K");
+        assert_eq!(Binding::Mouse(MouseButton::Right).label(), "Right mouse");
+    }
+}
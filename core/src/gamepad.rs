@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use ambient_api::{
+    input::{GamepadButton, Input},
+    prelude::*,
+};
+
+/// Stick movement below this is ignored, as sticks rarely rest exactly at the center.
+const STICK_DEADZONE: f32 = 0.15;
+
+/// The state of the first connected gamepad, kept between frames so that presses can be detected.
+#[derive(Default)]
+pub struct GamepadInput {
+    pressed: HashSet<GamepadButton>,
+    last_pressed: HashSet<GamepadButton>,
+    pub left_stick: Vec2,
+    pub right_stick: Vec2,
+}
+impl GamepadInput {
+    pub fn update(&mut self, input: &Input) {
+        self.last_pressed = std::mem::take(&mut self.pressed);
+
+        let Some(pad) = input.gamepads.iter().find(|pad| pad.connected) else {
+            self.left_stick = Vec2::ZERO;
+            self.right_stick = Vec2::ZERO;
+            return;
+        };
+        self.pressed = pad.buttons.iter().copied().collect();
+        self.left_stick = deadzone(pad.left_stick);
+        self.right_stick = deadzone(pad.right_stick);
+    }
+
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// Returns true if the button was pressed since the last update.
+    pub fn just_pressed(&self, button: GamepadButton) -> bool {
+        self.pressed.contains(&button) && !self.last_pressed.contains(&button)
+    }
+}
+
+/// Zeroes small stick movements, and rescales the rest so that the stick still reaches full speed.
+fn deadzone(stick: Vec2) -> Vec2 {
+    let length = stick.length();
+    if length < STICK_DEADZONE {
+        return Vec2::ZERO;
+    }
+
+    stick / length * ((length - STICK_DEADZONE) / (1. - STICK_DEADZONE)).min(1.)
+}
@@ -0,0 +1,93 @@
+use ambient_api::{
+    core::{
+        primitives::components::cube,
+        rendering::components::{color, transparency_group},
+        transform::components::{rotation, scale, translation},
+    },
+    prelude::*,
+};
+
+use crate::{
+    packages::{
+        tangent_rider_schema::components::level_hash,
+        this::components::{is_ghost_run, run_positions, run_rotations},
+    },
+    race_elapsed, recording, storage,
+    track::Track,
+};
+
+/// The directory ghost runs are saved in, in the server's storage.
+const GHOST_RUN_DIR: &str = "ghost_runs";
+
+/// Replays the ghost run if there is one.
+pub fn setup() {
+    query((run_positions(), run_rotations()))
+        .requires(is_ghost_run())
+        .each_frame(|ghosts| {
+            let elapsed = race_elapsed().unwrap_or_default().as_secs_f32();
+
+            for (ghost_id, (positions, rotations)) in ghosts {
+                let track = Track {
+                    interval: recording::SAMPLE_INTERVAL,
+                    positions,
+                    rotations,
+                };
+                let Some((position, rot)) = track.sample(elapsed) else {
+                    continue;
+                };
+
+                entity::set_component(ghost_id, translation(), position);
+                entity::set_component(ghost_id, rotation(), rot);
+            }
+        });
+}
+
+/// Spawns a ghost car replaying the best run on the current level, if one has been saved.
+pub fn spawn_best_run() {
+    despawn_all();
+
+    let Some(track) = entity::get_component(entity::synchronized_resources(), level_hash())
+        .and_then(|hash| storage::read(&path(&hash)))
+        .and_then(|bytes| Track::from_bytes(&bytes))
+    else {
+        return;
+    };
+    let Some((position, rot)) = track.sample(0.) else {
+        return;
+    };
+
+    // The ghost has no collider, so it can't get in anyone's way
+    Entity::new()
+        .with(cube(), ())
+        .with(translation(), position)
+        .with(rotation(), rot)
+        .with(scale(), vec3(1.2, 2.4, 0.6))
+        .with(color(), vec4(0.7, 0.9, 1.0, 0.35))
+        .with(transparency_group(), 0)
+        .with(is_ghost_run(), ())
+        .with(run_positions(), track.positions)
+        .with(run_rotations(), track.rotations)
+        .spawn();
+}
+
+/// Saves the run of the vehicle as the ghost run for the current level.
+pub fn save_run(vehicle_id: EntityId) {
+    let (Some(hash), Some(track)) = (
+        entity::get_component(entity::synchronized_resources(), level_hash()),
+        recording::track(vehicle_id),
+    ) else {
+        return;
+    };
+
+    storage::write(&path(&hash), track.to_bytes());
+}
+
+pub fn despawn_all() {
+    for id in entity::get_all(is_ghost_run()) {
+        entity::despawn(id);
+    }
+}
+
+fn path(level_hash: &str) -> String {
+    format!("{GHOST_RUN_DIR}/{level_hash}.trk")
+}
@@ -0,0 +1,158 @@
+use ambient_api::{
+    core::{physics::components::linear_velocity, transform::components::translation},
+    element::use_entity_component,
+    prelude::*,
+};
+
+use crate::{
+    controls::{Action, Controls},
+    format_race_time,
+    packages::{
+        tangent_rider_schema::components::{
+            active_players, alive_player_queue, is_end_platform, play_start_time,
+            player_finish_time, server_time, start_position,
+        },
+        tangent_schema::player::components as pc,
+    },
+    use_frame_value,
+    vehicle_camera::CameraMode,
+};
+
+/// Everything shown on the HUD, rounded to what is displayed so that it only changes when
+/// the HUD needs to be re-rendered.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct HudState {
+    speed: Option<u32>,
+    alive: usize,
+    total: usize,
+    /// The player's rank by progress towards the end platform, and how many players are ranked.
+    rank: Option<(usize, usize)>,
+    /// How far along the course the player is, from 0 to 100.
+    progress: Option<u32>,
+    camera: String,
+}
+impl HudState {
+    fn current() -> Self {
+        let resources = entity::synchronized_resources();
+        let local_player_id = player::get_local();
+        let alive_players =
+            entity::get_component(resources, alive_player_queue()).unwrap_or_default();
+        let total = entity::get_component(resources, active_players())
+            .unwrap_or_default()
+            .len();
+
+        let local_vehicle_id = entity::get_component(local_player_id, pc::vehicle_ref());
+        let speed = local_vehicle_id
+            .and_then(|id| entity::get_component(id, linear_velocity()))
+            .map(|velocity| velocity.length().round() as u32);
+
+        let mut progresses: Vec<(EntityId, f32)> = alive_players
+            .iter()
+            .filter_map(|&player_id| Some((player_id, progress(player_id)?)))
+            .collect();
+        progresses.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        let rank = progresses
+            .iter()
+            .position(|(id, _)| *id == local_player_id)
+            .map(|index| (index + 1, progresses.len()));
+        let progress = progresses
+            .iter()
+            .find(|(id, _)| *id == local_player_id)
+            .map(|(_, progress)| (progress.clamp(0., 1.) * 100.).round() as u32);
+
+        Self {
+            speed,
+            alive: alive_players.len(),
+            total,
+            rank,
+            progress,
+            camera: format!(
+                "Camera: {} ({} to switch)",
+                CameraMode::current().name(),
+                Controls::get().label(Action::SwitchCamera)
+            ),
+        }
+    }
+}
+
+/// Returns how far the player's vehicle is along the line from the start position to the
+/// end platform, where 0 is the start and 1 is the end.
+fn progress(player_id: EntityId) -> Option<f32> {
+    let start = entity::get_component(entity::synchronized_resources(), start_position())?;
+    let end = entity::get_all(is_end_platform())
+        .into_iter()
+        .find_map(|id| entity::get_component(id, translation()))?;
+    let vehicle_id = entity::get_component(player_id, pc::vehicle_ref())?;
+    let position = entity::get_component(vehicle_id, translation())?;
+
+    let line = end - start;
+    if line.length_squared() == 0. {
+        return None;
+    }
+    Some((position - start).dot(line) / line.length_squared())
+}
+
+#[element_component]
+pub fn Hud(_hooks: &mut Hooks) -> Element {
+    Group::el([RaceClock.el(), HudPanel.el()])
+}
+
+#[element_component]
+fn HudPanel(hooks: &mut Hooks) -> Element {
+    let HudState {
+        speed,
+        alive,
+        total,
+        rank,
+        progress,
+        camera,
+    } = use_frame_value(hooks, HudState::current);
+
+    WindowSized::el([FlowColumn::el([with_rect(
+        FlowColumn::el(
+            [
+                speed.map(|speed| Text::el(format!("{speed} m/s")).section_style()),
+                Some(Text::el(format!("Alive: {alive}/{total}"))),
+                rank.map(|(rank, ranked)| Text::el(format!("Position: {rank}/{ranked}"))),
+                progress.map(|progress| Text::el(format!("Progress: {progress}%"))),
+                Some(Text::el(camera)),
+            ]
+            .into_iter()
+            .flatten(),
+        )
+        .with(space_between_items(), 4.0),
+    )
+    .with_padding_even(8.0)
+    .with_background(vec4(0.0, 0.0, 0.0, 0.5))])
+    .with(fit_horizontal(), Fit::Parent)
+    .with(fit_vertical(), Fit::Parent)
+    .with(align_horizontal(), Align::End)
+    .with(align_vertical(), Align::End)
+    .with_padding_even(STREET)])
+}
+
+#[element_component]
+fn RaceClock(hooks: &mut Hooks) -> Element {
+    let start_time =
+        use_entity_component(hooks, entity::synchronized_resources(), play_start_time());
+    let finish_time = use_entity_component(hooks, player::get_local(), player_finish_time());
+    let label = use_frame_value(hooks, move || {
+        let now = entity::get_component(entity::synchronized_resources(), server_time());
+        match (finish_time, start_time.zip(now)) {
+            (Some(finish_time), _) => Some(format!("Finished: {}", format_race_time(finish_time))),
+            (None, Some((start_time, now))) => now.checked_sub(start_time).map(format_race_time),
+            _ => None,
+        }
+    });
+
+    let Some(label) = label else {
+        return Element::new();
+    };
+
+    WindowSized::el([FlowColumn::el([with_rect(Text::el(label).section_style())
+        .with_padding_even(8.0)
+        .with_background(vec4(0.0, 0.0, 0.0, 0.5))])
+    .with(fit_horizontal(), Fit::Parent)
+    .with(align_horizontal(), Align::Center)
+    .with_padding_even(STREET)])
+}
@@ -0,0 +1,178 @@
+use ambient_api::prelude::*;
+
+use crate::{
+    packages::tangent_rider_schema::components::{
+        player_finish_time, player_income_amounts, player_income_sources, player_score,
+        player_trap_kills,
+    },
+    teams,
+};
+
+/// The amount every player receives each construction phase.
+const BASE_INCOME: u32 = 500;
+/// The amount a player receives for reaching the end platform in the last play phase.
+const FINISH_BONUS: u32 = 300;
+/// The amount a player receives for each player killed by their objects in the last play phase.
+const TRAP_KILL_BONUS: u32 = 100;
+/// The fraction of unspent money paid out as interest.
+const INTEREST_RATE: f32 = 0.1;
+/// The most interest a player can receive in a single construction phase.
+const INTEREST_CAP: u32 = 100;
+/// The amount received by the players with the lowest score, if not everyone is tied.
+const CATCH_UP_BONUS: u32 = 150;
+
+/// A source of income that is paid out at the start of each construction phase.
+pub struct IncomeRule {
+    /// The name shown to the player in their income breakdown.
+    pub name: &'static str,
+    /// Calculates how much the given player receives from this rule.
+    pub amount: fn(&IncomeContext, EntityId) -> u32,
+}
+
+/// The rules used to pay players. Add to this list to introduce a new source of income.
+pub const INCOME_RULES: &[IncomeRule] = &[
+    IncomeRule {
+        name: "Base income",
+        amount: base_income,
+    },
+    IncomeRule {
+        name: "Finish bonus",
+        amount: finish_bonus,
+    },
+    IncomeRule {
+        name: "Trap kills",
+        amount: trap_kill_bonus,
+    },
+    IncomeRule {
+        name: "Interest",
+        amount: interest,
+    },
+    IncomeRule {
+        name: "Catch-up bonus",
+        amount: catch_up_bonus,
+    },
+];
+
+fn base_income(_ctx: &IncomeContext, _player_id: EntityId) -> u32 {
+    BASE_INCOME
+}
+
+fn finish_bonus(_ctx: &IncomeContext, player_id: EntityId) -> u32 {
+    if entity::has_component(player_id, player_finish_time()) {
+        FINISH_BONUS
+    } else {
+        0
+    }
+}
+
+fn trap_kill_bonus(_ctx: &IncomeContext, player_id: EntityId) -> u32 {
+    entity::get_component(player_id, player_trap_kills()).unwrap_or_default() * TRAP_KILL_BONUS
+}
+
+fn interest(_ctx: &IncomeContext, player_id: EntityId) -> u32 {
+    // Teammates share a wallet, so they also share its interest.
+    interest_on(teams::money(player_id) / teams::wallet_size(player_id) as u32)
+}
+
+fn interest_on(money: u32) -> u32 {
+    ((money as f32 * INTEREST_RATE) as u32).min(INTEREST_CAP)
+}
+
+fn catch_up_bonus(ctx: &IncomeContext, player_id: EntityId) -> u32 {
+    if ctx.is_trailing(player_id) {
+        CATCH_UP_BONUS
+    } else {
+        0
+    }
+}
+
+/// Game-wide state that income rules can use to make their decisions.
+pub struct IncomeContext {
+    lowest_score: u32,
+    everyone_tied: bool,
+}
+impl IncomeContext {
+    fn new(players: &[EntityId]) -> Self {
+        Self::from_scores(&players.iter().map(|id| score(*id)).collect::<Vec<_>>())
+    }
+
+    fn from_scores(scores: &[u32]) -> Self {
+        let lowest_score = scores.iter().copied().min().unwrap_or_default();
+
+        Self {
+            lowest_score,
+            everyone_tied: scores.iter().all(|score| *score == lowest_score),
+        }
+    }
+
+    /// Returns true if this player has the lowest score and at least one player is ahead of them.
+    pub fn is_trailing(&self, player_id: EntityId) -> bool {
+        self.is_trailing_score(score(player_id))
+    }
+
+    fn is_trailing_score(&self, score: u32) -> bool {
+        !self.everyone_tied && score == self.lowest_score
+    }
+}
+
+/// Pays each player according to the [INCOME_RULES], and records the breakdown on the player.
+pub fn pay_income(players: &[EntityId]) {
+    let ctx = IncomeContext::new(players);
+
+    // Calculate every player's income before paying anyone, so that rules see the money left
+    // over from the last construction phase (even when teammates share a wallet).
+    let breakdowns: Vec<(Vec<String>, Vec<u32>)> = players
+        .iter()
+        .map(|&player_id| {
+            INCOME_RULES
+                .iter()
+                .map(|rule| (rule.name, (rule.amount)(&ctx, player_id)))
+                .filter(|(_, amount)| *amount > 0)
+                .map(|(name, amount)| (name.to_string(), amount))
+                .unzip()
+        })
+        .collect();
+
+    for (&player_id, (sources, amounts)) in players.iter().zip(breakdowns) {
+        teams::deposit(player_id, amounts.iter().sum());
+        entity::add_components(
+            player_id,
+            Entity::new()
+                .with(player_income_sources(), sources)
+                .with(player_income_amounts(), amounts),
+        );
+    }
+}
+
+fn score(player_id: EntityId) -> u32 {
+    entity::get_component(player_id, player_score()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interest_is_capped() {
+        assert_eq!(interest_on(0), 0);
+        assert_eq!(interest_on(9), 0);
+        assert_eq!(interest_on(450), 45);
+        assert_eq!(interest_on(1000), INTEREST_CAP);
+        assert_eq!(interest_on(u32::MAX), INTEREST_CAP);
+    }
+
+    #[test]
+    fn lowest_scores_are_trailing() {
+        let ctx = IncomeContext::from_scores(&[3, 1, 5, 1]);
+        assert!(ctx.is_trailing_score(1));
+        assert!(!ctx.is_trailing_score(3));
+        assert!(!ctx.is_trailing_score(5));
+    }
+
+    #[test]
+    fn nobody_is_trailing_when_tied() {
+        assert!(!IncomeContext::from_scores(&[2, 2, 2]).is_trailing_score(2));
+        assert!(!IncomeContext::from_scores(&[0]).is_trailing_score(0));
+        assert!(!IncomeContext::from_scores(&[]).is_trailing_score(0));
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use ambient_api::{core::player::components::user_id, prelude::*};
+
+use crate::{
+    packages::this::messages::{InputSettings, RequestInputSettings},
+    storage,
+};
+
+/// The name each player's input settings are saved under in the server's storage.
+const INPUT_SETTINGS_FILE: &str = "input_settings.tsv";
+
+/// Stores each player's input settings, so that their bindings follow them between sessions.
+/// Client modules have nowhere to save them, so the server keeps them on their behalf.
+pub fn setup() {
+    RequestInputSettings::subscribe(|ctx, _msg| {
+        let Some(uid) = ctx
+            .client_entity_id()
+            .and_then(|player_id| entity::get_component(player_id, user_id()))
+        else {
+            return;
+        };
+
+        // Players without saved settings use the defaults
+        if let Some(settings) = load().remove(&uid) {
+            InputSettings { settings }.send_client_targeted_reliable(uid);
+        }
+    });
+
+    InputSettings::subscribe(|ctx, msg| {
+        let Some(uid) = ctx
+            .client_entity_id()
+            .and_then(|player_id| entity::get_component(player_id, user_id()))
+        else {
+            return;
+        };
+
+        let mut all_settings = load();
+        all_settings.insert(uid, msg.settings);
+        storage::write(INPUT_SETTINGS_FILE, to_tsv(&all_settings));
+    });
+}
+
+/// Loads every player's saved settings, keyed by user ID.
+fn load() -> HashMap<String, String> {
+    storage::read_to_string(INPUT_SETTINGS_FILE)
+        .map(|contents| parse(&contents))
+        .unwrap_or_default()
+}
+
+/// Reads settings produced by [to_tsv]. Malformed lines are skipped.
+fn parse(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (uid, settings) = line.split_once('\t')?;
+            Some((
+                storage::unescape_field(uid)?,
+                storage::unescape_field(settings)?,
+            ))
+        })
+        .collect()
+}
+
+/// Writes one line per player, with their user ID and settings separated by a tab.
+fn to_tsv(all_settings: &HashMap<String, String>) -> String {
+    all_settings
+        .iter()
+        .map(|(uid, settings)| {
+            format!(
+                "{}\t{}\n",
+                storage::escape_field(uid),
+                storage::escape_field(settings)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let all_settings = HashMap::from([
+            (
+                "player".to_string(),
+                "sensitivity=1.5\ninvert_pitch=true\nJump=Space\n".to_string(),
+            ),
+            ("tab\tuser".to_string(), String::new()),
+        ]);
+        assert_eq!(parse(&to_tsv(&all_settings)), all_settings);
+    }
+
+    #[test]
+    fn parse_skips_malformed_lines() {
+        let all_settings = parse("player\tJump=Space\\n\nno settings\nbad\\escape\tJump=Space\n");
+        assert_eq!(
+            all_settings,
+            HashMap::from([("player".to_string(), "Jump=Space\n".to_string())])
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use ambient_api::{
+    core::{
+        player::components::user_id,
+        transform::components::{rotation, scale, translation},
+    },
+    prelude::*,
+};
+
+use crate::{
+    packages::tangent_rider_schema::components::{
+        is_end_platform, is_spawned, is_start_platform, level_hash, level_record_holder,
+        level_record_time, placed_position, placed_rotation, player_best_time,
+        player_new_level_record, player_new_personal_best, spawnable_name, spawned_from,
+    },
+    storage,
+};
+
+/// The name the leaderboard is saved under in the server's storage.
+const LEADERBOARD_FILE: &str = "leaderboard.tsv";
+
+/// The best time of each player on each level, keyed by level hash and then user ID.
+#[derive(Default)]
+struct Leaderboard(HashMap<String, HashMap<String, Duration>>);
+impl Leaderboard {
+    /// Loads the saved leaderboard, or an empty one if none has been saved.
+    fn load() -> Self {
+        storage::read_to_string(LEADERBOARD_FILE)
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::write(LEADERBOARD_FILE, self.to_tsv());
+    }
+
+    /// Reads a leaderboard produced by [Self::to_tsv]. Malformed lines are skipped.
+    fn parse(contents: &str) -> Self {
+        let mut leaderboard = Self::default();
+        for line in contents.lines() {
+            let mut fields = line.split('\t');
+            let (Some(level), Some(uid), Some(millis), None) = (
+                fields.next().and_then(storage::unescape_field),
+                fields.next().and_then(storage::unescape_field),
+                fields.next().and_then(|millis| millis.parse().ok()),
+                fields.next(),
+            ) else {
+                continue;
+            };
+
+            leaderboard
+                .0
+                .entry(level)
+                .or_default()
+                .insert(uid, Duration::from_millis(millis));
+        }
+        leaderboard
+    }
+
+    /// Writes one line per time, with tab-separated level hash, user ID and time in milliseconds.
+    fn to_tsv(&self) -> String {
+        self.0
+            .iter()
+            .flat_map(|(level, times)| {
+                times.iter().map(move |(uid, time)| {
+                    format!(
+                        "{}\t{}\t{}\n",
+                        storage::escape_field(level),
+                        storage::escape_field(uid),
+                        time.as_millis()
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the holder and time of the record for the level.
+    fn record(&self, level: &str) -> Option<(String, Duration)> {
+        self.0
+            .get(level)?
+            .iter()
+            .min_by_key(|(_, time)| **time)
+            .map(|(uid, time)| (uid.clone(), *time))
+    }
+}
+
+/// Identifies the level that is about to be played, and loads its records for the given players.
+pub fn start_level(players: &[EntityId]) {
+    let hash = hash_level();
+    let leaderboard = Leaderboard::load();
+
+    let resources = entity::synchronized_resources();
+    match leaderboard.record(&hash) {
+        Some((holder, time)) => {
+            entity::add_component(resources, level_record_holder(), holder);
+            entity::add_component(resources, level_record_time(), time);
+        }
+        None => {
+            entity::remove_component(resources, level_record_holder());
+            entity::remove_component(resources, level_record_time());
+        }
+    }
+
+    for &player_id in players {
+        entity::remove_components(
+            player_id,
+            &[&player_new_personal_best(), &player_new_level_record()],
+        );
+
+        let best_time = entity::get_component(player_id, user_id())
+            .and_then(|uid| leaderboard.0.get(&hash)?.get(&uid).copied());
+        match best_time {
+            Some(time) => entity::add_component(player_id, player_best_time(), time),
+            None => entity::remove_component(player_id, player_best_time()),
+        }
+    }
+
+    entity::add_component(resources, level_hash(), hash);
+}
+
+/// Saves the player's finish time if it's their best on this level, and marks them if they set a
+/// personal best or a level record.
+pub fn record_finish(player_id: EntityId, time: Duration) {
+    let resources = entity::synchronized_resources();
+    let (Some(hash), Some(uid)) = (
+        entity::get_component(resources, level_hash()),
+        entity::get_component(player_id, user_id()),
+    ) else {
+        return;
+    };
+
+    let mut leaderboard = Leaderboard::load();
+    let record = leaderboard.record(&hash);
+    let times = leaderboard.0.entry(hash).or_default();
+    if times.get(&uid).is_some_and(|best| *best <= time) {
+        return;
+    }
+
+    times.insert(uid.clone(), time);
+    leaderboard.save();
+
+    entity::add_component(player_id, player_best_time(), time);
+    entity::add_component(player_id, player_new_personal_best(), ());
+    if record.map_or(true, |(_, record_time)| time < record_time) {
+        entity::add_component(player_id, player_new_level_record(), ());
+        entity::add_component(resources, level_record_holder(), uid);
+        entity::add_component(resources, level_record_time(), time);
+    }
+}
+
+/// Hashes the platforms and placed objects, so that the same level always produces the same hash.
+fn hash_level() -> String {
+    let mut object_hashes: Vec<u64> = [
+        entity::get_all(is_start_platform()),
+        entity::get_all(is_end_platform()),
+        entity::get_all(is_spawned()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|id| {
+        let mut hasher = StableHasher::default();
+
+        let kind = if entity::has_component(id, is_start_platform()) {
+            Some("start".to_string())
+        } else if entity::has_component(id, is_end_platform()) {
+            Some("end".to_string())
+        } else {
+            entity::get_component(id, spawned_from())
+                .and_then(|spawnable_id| entity::get_component(spawnable_id, spawnable_name()))
+        };
+        kind.hash(&mut hasher);
+
+        // Animated objects are never in the same pose twice, so use how they were placed instead
+        let position = entity::get_component(id, placed_position())
+            .or_else(|| entity::get_component(id, translation()))
+            .unwrap_or_default();
+        let size = entity::get_component(id, scale()).unwrap_or(Vec3::ONE);
+        let orientation = Vec4::from(
+            entity::get_component(id, placed_rotation())
+                .or_else(|| entity::get_component(id, rotation()))
+                .unwrap_or_default(),
+        );
+        for value in position
+            .to_array()
+            .into_iter()
+            .chain(size.to_array())
+            .chain(orientation.to_array())
+        {
+            // Quantize to the millimetre, so that floating-point noise doesn't change the hash
+            ((value * 1000.).round() as i64).hash(&mut hasher);
+        }
+
+        hasher.finish()
+    })
+    .collect();
+
+    // Objects can be spawned in any order, so sort them to make the hash order-independent
+    object_hashes.sort_unstable();
+    let mut hasher = StableHasher::default();
+    object_hashes.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// An FNV-1a hasher. Unlike the standard library's default hasher, its output is guaranteed not
+/// to change between Rust versions, which matters as level hashes are saved to disk.
+struct StableHasher(u64);
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut leaderboard = Leaderboard::default();
+        let times = leaderboard
+            .0
+            .entry("0123456789abcdef".to_string())
+            .or_default();
+        times.insert("plain".to_string(), Duration::from_millis(61_250));
+        times.insert("tab\tand\nnewline".to_string(), Duration::from_millis(5));
+        leaderboard
+            .0
+            .entry("fedcba9876543210".to_string())
+            .or_default()
+            .insert("back\\slash".to_string(), Duration::from_secs(3600));
+
+        assert_eq!(Leaderboard::parse(&leaderboard.to_tsv()).0, leaderboard.0);
+    }
+
+    #[test]
+    fn parse_skips_malformed_lines() {
+        let leaderboard = Leaderboard::parse(
+            "level\tuid\t1000\n\
+             level\tmissing time\n\
+             level\tbad time\tsoon\n\
+             level\textra\t1000\tfield\n\
+             level\tbad\\escape\t1000\n",
+        );
+        assert_eq!(leaderboard.0.len(), 1);
+        assert_eq!(leaderboard.0["level"].len(), 1);
+        assert_eq!(leaderboard.0["level"]["uid"], Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn record_is_fastest_time() {
+        let leaderboard = Leaderboard::parse("level\tslow\t2000\nlevel\tfast\t1000\n");
+        assert_eq!(
+            leaderboard.record("level"),
+            Some(("fast".to_string(), Duration::from_millis(1000)))
+        );
+        assert_eq!(leaderboard.record("other"), None);
+    }
+}
@@ -0,0 +1,283 @@
+use ambient_api::{
+    core::{
+        camera::components::{aspect_ratio, fovy},
+        layout::components::{height, width},
+        transform::components::{local_to_world, lookat_target, rotation, scale, translation},
+    },
+    prelude::*,
+};
+
+use crate::{
+    packages::{
+        tangent_rider_schema::components::{
+            is_end_platform, is_spawned, player_current_spawnable_ghost, start_position,
+        },
+        tangent_schema::{player::components as pc, vehicle::components::is_vehicle},
+        this::components::{is_ghost_run, minimap_camera_target},
+    },
+    use_frame_value,
+};
+
+/// The width and height of the minimap, in pixels.
+const MINIMAP_SIZE: f32 = 200.0;
+/// The space around the level shown on the minimap, in metres.
+const MINIMAP_MARGIN: f32 = 20.0;
+/// How long the lines showing the camera's view are, in pixels.
+const FRUSTUM_LENGTH: f32 = 30.0;
+
+/// A rectangle drawn on the minimap, in pixels. Quantized, so that the minimap is only
+/// re-rendered when something visibly moves.
+#[derive(Clone, Debug, PartialEq)]
+struct Shape {
+    center: IVec2,
+    size: IVec2,
+    /// The rotation of the shape, in whole degrees.
+    angle: i32,
+    color: Vec4,
+}
+impl Shape {
+    fn el(&self) -> Element {
+        let size = self.size.as_vec2();
+        let rot = Quat::from_rotation_z((self.angle as f32).to_radians());
+        // Rectangles rotate around their top-left corner, so offset them to rotate around the center
+        let corner = self.center.as_vec2().extend(0.) - rot * (size / 2.).extend(0.);
+
+        Rectangle
+            .el()
+            .with(width(), size.x)
+            .with(height(), size.y)
+            .with_background(self.color)
+            .with(translation(), corner + Vec3::Z * -0.01)
+            .with(rotation(), rot)
+    }
+
+    /// Keeps small objects, like vehicles, visible when the level is zoomed out.
+    fn at_least(mut self, pixels: i32) -> Self {
+        self.size = self.size.max(IVec2::splat(pixels));
+        self
+    }
+}
+
+/// Maps between level positions and positions on the minimap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MapTransform {
+    min: Vec2,
+    /// Pixels per metre.
+    scale: f32,
+}
+impl MapTransform {
+    /// Fits the start position, end platform and all spawned objects onto the minimap.
+    fn fit_level() -> Option<Self> {
+        let points: Vec<Vec2> =
+            entity::get_component(entity::synchronized_resources(), start_position())
+                .into_iter()
+                .chain(
+                    [
+                        entity::get_all(is_end_platform()),
+                        entity::get_all(is_spawned()),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| entity::get_component(id, translation())),
+                )
+                .map(|position| position.truncate())
+                .collect();
+
+        let min = points.iter().copied().reduce(Vec2::min)? - MINIMAP_MARGIN;
+        let max = points.iter().copied().reduce(Vec2::max)? + MINIMAP_MARGIN;
+        let extent = (max - min).max_element();
+
+        // Center the level on the map, keeping the aspect ratio
+        Some(Self {
+            min: (min + max) / 2. - extent / 2.,
+            scale: MINIMAP_SIZE / extent,
+        })
+    }
+
+    fn to_map(self, position: Vec3) -> Vec2 {
+        (position.truncate() - self.min) * self.scale
+    }
+
+    fn to_level(self, map_position: Vec2) -> Vec2 {
+        map_position / self.scale + self.min
+    }
+
+    fn shape(self, position: Vec3, size: Vec2, rot: Quat, color: Vec4) -> Shape {
+        let forward = rot * Vec3::X;
+        Shape {
+            center: self.to_map(position).round().as_ivec2(),
+            size: (size * self.scale).max(Vec2::splat(2.)).round().as_ivec2(),
+            angle: forward.y.atan2(forward.x).to_degrees().round() as i32,
+            color,
+        }
+    }
+
+    /// A thin rectangle from one point on the map to another.
+    fn line(self, from: Vec2, to: Vec2, color: Vec4) -> Shape {
+        let direction = to - from;
+        Shape {
+            center: ((from + to) / 2.).round().as_ivec2(),
+            size: ivec2(direction.length().round() as i32, 2),
+            angle: direction.y.atan2(direction.x).to_degrees().round() as i32,
+            color,
+        }
+    }
+}
+
+/// Everything currently drawn on the minimap.
+#[derive(Clone, Debug, PartialEq)]
+struct MinimapState {
+    transform: MapTransform,
+    shapes: Vec<Shape>,
+}
+impl MinimapState {
+    fn current(camera_id: EntityId) -> Option<Self> {
+        let transform = MapTransform::fit_level()?;
+        let mut shapes = vec![];
+
+        for id in entity::get_all(is_spawned()) {
+            let (Some(position), Some(rot)) = (
+                entity::get_component(id, translation()),
+                entity::get_component(id, rotation()),
+            ) else {
+                continue;
+            };
+            let size = entity::get_component(id, scale()).unwrap_or(Vec3::ONE);
+            shapes.push(transform.shape(position, size.truncate(), rot, vec4(0.6, 0.6, 0.6, 1.0)));
+        }
+
+        // Objects that players are still placing are see-through, and the local player's is
+        // brighter, so that it's easy to tell what's being built where
+        let local_ghost_id =
+            entity::get_component(player::get_local(), player_current_spawnable_ghost());
+        let ghosts = entity::get_all(player_current_spawnable_ghost())
+            .into_iter()
+            .filter_map(|player_id| {
+                entity::get_component(player_id, player_current_spawnable_ghost())
+            });
+        for id in ghosts {
+            let (Some(position), Some(rot)) = (
+                entity::get_component(id, translation()),
+                entity::get_component(id, rotation()),
+            ) else {
+                continue;
+            };
+            let size = entity::get_component(id, scale()).unwrap_or(Vec3::ONE);
+            let color = if Some(id) == local_ghost_id {
+                vec4(1.0, 0.6, 0.2, 0.7)
+            } else {
+                vec4(1.0, 0.6, 0.2, 0.35)
+            };
+            shapes.push(transform.shape(position, size.truncate(), rot, color));
+        }
+
+        if let Some(position) =
+            entity::get_component(entity::synchronized_resources(), start_position())
+        {
+            shapes.push(transform.shape(
+                position,
+                Vec2::splat(10.),
+                Quat::IDENTITY,
+                vec4(0.9, 0.2, 0.2, 1.0),
+            ));
+        }
+        for id in entity::get_all(is_end_platform()) {
+            let Some(position) = entity::get_component(id, translation()) else {
+                continue;
+            };
+            shapes.push(transform.shape(
+                position,
+                Vec2::splat(10.),
+                Quat::IDENTITY,
+                vec4(0.2, 0.9, 0.2, 1.0),
+            ));
+        }
+
+        let local_vehicle_id = entity::get_component(player::get_local(), pc::vehicle_ref());
+        let vehicles = entity::get_all(is_vehicle())
+            .into_iter()
+            .map(|id| {
+                let color = if Some(id) == local_vehicle_id {
+                    vec4(1.0, 1.0, 0.2, 1.0)
+                } else {
+                    vec4(1.0, 1.0, 1.0, 1.0)
+                };
+                (id, color)
+            })
+            .chain(
+                entity::get_all(is_ghost_run())
+                    .into_iter()
+                    .map(|id| (id, vec4(0.7, 0.9, 1.0, 0.6))),
+            );
+        for (id, color) in vehicles {
+            let (Some(position), Some(rot)) = (
+                entity::get_component(id, translation()),
+                entity::get_component(id, rotation()),
+            ) else {
+                continue;
+            };
+            shapes.push(
+                transform
+                    .shape(position, vec2(1.2, 2.4), rot, color)
+                    .at_least(4),
+            );
+        }
+
+        // Show what the camera can see as two lines spreading out from its position
+        if let (Some(position), Some(target)) = (
+            entity::get_component(camera_id, translation()),
+            entity::get_component(camera_id, lookat_target()),
+        ) {
+            let origin = transform.to_map(position);
+            let forward = (target - position).truncate().normalize_or_zero();
+            let fovy = entity::get_component(camera_id, fovy()).unwrap_or(1.0);
+            let aspect_ratio = entity::get_component(camera_id, aspect_ratio()).unwrap_or(1.0);
+            let half_fov = ((fovy / 2.).tan() * aspect_ratio).atan();
+            for angle in [-half_fov, half_fov] {
+                let edge = Vec2::from_angle(angle).rotate(forward) * FRUSTUM_LENGTH;
+                shapes.push(transform.line(origin, origin + edge, vec4(1.0, 1.0, 0.2, 0.8)));
+            }
+        }
+
+        Some(Self { transform, shapes })
+    }
+}
+
+/// A top-down map of the level. If `clickable` is set, clicking it moves the construction camera.
+#[element_component]
+pub fn Minimap(hooks: &mut Hooks, camera_id: EntityId, clickable: bool) -> Element {
+    let state = use_frame_value(hooks, move || MinimapState::current(camera_id));
+    let Some(MinimapState { transform, shapes }) = state else {
+        return Element::new();
+    };
+
+    let map = Element::new()
+        .children(shapes.iter().map(Shape::el).collect())
+        .with(width(), MINIMAP_SIZE)
+        .with(height(), MINIMAP_SIZE);
+    let map = with_rect(map).with_background(vec4(0.0, 0.1, 0.3, 0.7));
+    let map = if clickable {
+        ClickArea::new(map)
+            .on_mouse_down(move |_, id, button| {
+                if button != MouseButton::Left {
+                    return;
+                }
+
+                let origin = entity::get_component(id, local_to_world())
+                    .unwrap_or_default()
+                    .to_scale_rotation_translation()
+                    .2
+                    .truncate();
+                let target = transform.to_level(input::get().mouse_position - origin);
+                entity::add_component(entity::resources(), minimap_camera_target(), target);
+            })
+            .el()
+    } else {
+        map
+    };
+
+    WindowSized::el([FlowColumn::el([map])
+        .with(fit_horizontal(), Fit::Parent)
+        .with(align_horizontal(), Align::End)
+        .with_padding_even(STREET)])
+}
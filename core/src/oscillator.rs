@@ -0,0 +1,29 @@
+use ambient_api::prelude::*;
+
+use crate::packages::tangent_rider_schema::components::{
+    oscillator_angle, oscillator_period, oscillator_pivot,
+};
+
+/// An object that swings back and forth around a pivot.
+pub struct Oscillator {
+    pub angle: Vec3,
+    pub period: f32,
+    pub pivot: Vec3,
+}
+impl Oscillator {
+    pub fn get(id: EntityId) -> Option<Self> {
+        Some(Self {
+            angle: entity::get_component(id, oscillator_angle())?,
+            period: entity::get_component(id, oscillator_period())?,
+            pivot: entity::get_component(id, oscillator_pivot()).unwrap_or_default(),
+        })
+    }
+
+    /// Returns how far the centre has moved from rest and how far the object has turned, in its
+    /// placed orientation. `extent` runs from -1 (furthest one way) to 1 (furthest the other).
+    pub fn swing(&self, extent: f32) -> (Vec3, Quat) {
+        let angle = self.angle * extent;
+        let rot = Quat::from_scaled_axis(vec3(angle.y, angle.z, angle.x));
+        (self.pivot - rot * self.pivot, rot)
+    }
+}
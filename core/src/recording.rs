@@ -0,0 +1,77 @@
+use ambient_api::{
+    core::transform::components::{rotation, translation},
+    prelude::*,
+};
+
+use crate::{
+    animation,
+    packages::{
+        tangent_rider_schema::components::is_spawned,
+        tangent_schema::vehicle::components::is_vehicle,
+        this::components::{run_positions, run_rotations},
+    },
+    race_elapsed,
+    track::Track,
+    vanishing,
+};
+
+/// How often positions are sampled during a race, in seconds.
+pub const SAMPLE_INTERVAL: f32 = 0.1;
+
+/// Records the positions of vehicles and animated or vanishing objects during the race.
+pub fn setup() {
+    query((translation(), rotation()))
+        .requires(is_vehicle())
+        .each_frame(|vehicles| record(vehicles));
+
+    query((translation(), rotation()))
+        .requires(is_spawned())
+        .each_frame(|objects| {
+            record(
+                objects
+                    .into_iter()
+                    .filter(|(id, _)| animation::is_animated(*id) || vanishing::is_vanishing(*id))
+                    .collect(),
+            )
+        });
+}
+
+/// Removes the recordings of all animated objects, so that they can be recorded again.
+pub fn reset_objects() {
+    for id in entity::get_all(is_spawned()) {
+        entity::remove_components(id, &[&run_positions(), &run_rotations()]);
+    }
+}
+
+/// Returns the recording of the entity so far.
+pub fn track(id: EntityId) -> Option<Track> {
+    Some(Track {
+        interval: SAMPLE_INTERVAL,
+        positions: entity::get_component(id, run_positions())?,
+        rotations: entity::get_component(id, run_rotations())?,
+    })
+}
+
+fn record(entities: Vec<(EntityId, (Vec3, Quat))>) {
+    let Some(elapsed) = race_elapsed() else {
+        return;
+    };
+    let sample_count = (elapsed.as_secs_f32() / SAMPLE_INTERVAL) as usize + 1;
+
+    for (id, (position, rot)) in entities {
+        // If a frame took longer than the sample interval, repeat the current sample
+        // to keep the recording aligned to the race clock.
+        entity::mutate_component_with_default(
+            id,
+            run_positions(),
+            vec![position; sample_count],
+            |positions| positions.resize(sample_count, position),
+        );
+        entity::mutate_component_with_default(
+            id,
+            run_rotations(),
+            vec![rot; sample_count],
+            |rotations| rotations.resize(sample_count, rot),
+        );
+    }
+}
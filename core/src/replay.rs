@@ -0,0 +1,97 @@
+use ambient_api::prelude::*;
+
+use crate::{
+    packages::{
+        tangent_rider_schema::components::{is_spawned, player_finish_time},
+        tangent_schema::vehicle::components::{self as vc, is_vehicle},
+        this::components::{
+            is_replay_track, replay_finished, replay_is_vehicle, replay_positions,
+            replay_recording, replay_rotations, replay_sample_interval, replay_subject,
+            run_positions, run_rotations,
+        },
+    },
+    recording,
+    track::Track,
+};
+
+/// Keeps the paths of vehicles that are destroyed during the round for the replay.
+pub fn setup() {
+    entity::add_component(
+        entity::synchronized_resources(),
+        replay_sample_interval(),
+        recording::SAMPLE_INTERVAL,
+    );
+
+    despawn_query((vc::driver_ref(), run_positions(), run_rotations()))
+        .requires(is_vehicle())
+        .bind(|vehicles| {
+            if !entity::has_component(entity::synchronized_resources(), replay_recording()) {
+                return;
+            }
+
+            for (_vehicle_id, (driver_id, positions, rotations)) in vehicles {
+                spawn_vehicle_track(
+                    driver_id,
+                    Track {
+                        interval: recording::SAMPLE_INTERVAL,
+                        positions,
+                        rotations,
+                    },
+                );
+            }
+        });
+}
+
+/// Discards the last round's replay and starts recording a new one.
+pub fn start() {
+    clear();
+    recording::reset_objects();
+    entity::add_component(entity::synchronized_resources(), replay_recording(), ());
+}
+
+/// Stops recording, and keeps the paths of everything that is still in the level for the replay.
+pub fn finish() {
+    entity::remove_component(entity::synchronized_resources(), replay_recording());
+
+    for (vehicle_id, driver_id) in query(vc::driver_ref())
+        .requires(is_vehicle())
+        .build()
+        .evaluate()
+    {
+        if let Some(track) = recording::track(vehicle_id) {
+            spawn_vehicle_track(driver_id, track);
+        }
+    }
+
+    // Only animated objects are recorded
+    for object_id in entity::get_all(is_spawned()) {
+        if let Some(track) = recording::track(object_id) {
+            spawn_track(object_id, track);
+        }
+    }
+}
+
+/// Discards the replay.
+pub fn clear() {
+    entity::remove_component(entity::synchronized_resources(), replay_recording());
+    for id in entity::get_all(is_replay_track()) {
+        entity::despawn(id);
+    }
+}
+
+fn spawn_vehicle_track(driver_id: EntityId, track: Track) {
+    let track_id = spawn_track(driver_id, track);
+    entity::add_component(track_id, replay_is_vehicle(), ());
+    if entity::has_component(driver_id, player_finish_time()) {
+        entity::add_component(track_id, replay_finished(), ());
+    }
+}
+
+fn spawn_track(subject_id: EntityId, track: Track) -> EntityId {
+    Entity::new()
+        .with(is_replay_track(), ())
+        .with(replay_subject(), subject_id)
+        .with(replay_positions(), track.positions)
+        .with(replay_rotations(), track.rotations)
+        .spawn()
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use ambient_api::{
+    core::{
+        primitives::components::cube,
+        rendering::components::color,
+        transform::components::{rotation, scale, translation},
+    },
+    element::use_entity_component,
+    prelude::*,
+};
+
+use crate::{
+    controls::Controls,
+    format_race_time,
+    packages::{
+        tangent_rider_schema::components::{player_team, team_color},
+        this::components::{
+            is_replay_track, replay_duration, replay_finished, replay_is_vehicle, replay_positions,
+            replay_rotations, replay_sample_interval, replay_speed, replay_subject, replay_time,
+        },
+    },
+    track::Track,
+    use_frame_value, FlyCamera,
+};
+
+/// How long to wait at the end of the replay before it loops, in seconds.
+const LOOP_DELAY: f32 = 2.0;
+/// The speeds that the replay can be played back at.
+const SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Plays back the replay of the last round, while the player flies around freely.
+pub struct ReplayViewer {
+    camera: FlyCamera,
+    tracks: HashMap<EntityId, ReplayTrack>,
+    time: f32,
+}
+impl Default for ReplayViewer {
+    fn default() -> Self {
+        entity::add_component(entity::resources(), replay_speed(), 1.0);
+        Self {
+            camera: default(),
+            tracks: HashMap::new(),
+            time: 0.,
+        }
+    }
+}
+impl Drop for ReplayViewer {
+    fn drop(&mut self) {
+        for car_id in self.tracks.values().filter_map(|track| track.car_id) {
+            entity::despawn(car_id);
+        }
+    }
+}
+impl ReplayViewer {
+    pub fn tick(&mut self, camera_id: EntityId) {
+        let (delta, input) = input::get_delta();
+        self.camera.tick(camera_id, &delta, &input, false);
+
+        self.load_new_tracks();
+
+        let speed = entity::get_component(entity::resources(), replay_speed()).unwrap_or(1.0);
+        let duration = self
+            .tracks
+            .values()
+            .map(|track| track.track.duration())
+            .fold(0., f32::max);
+        self.time += delta_time() * speed;
+        if self.time > duration + LOOP_DELAY {
+            self.time = 0.;
+        }
+
+        entity::add_component(entity::resources(), replay_time(), self.time.min(duration));
+        entity::add_component(entity::resources(), replay_duration(), duration);
+
+        for track in self.tracks.values_mut() {
+            track.show(self.time);
+        }
+    }
+
+    fn load_new_tracks(&mut self) {
+        let interval =
+            entity::get_component(entity::synchronized_resources(), replay_sample_interval())
+                .unwrap_or(0.1);
+
+        for track_id in entity::get_all(is_replay_track()) {
+            if self.tracks.contains_key(&track_id) {
+                continue;
+            }
+
+            let (Some(subject_id), Some(positions), Some(rotations)) = (
+                entity::get_component(track_id, replay_subject()),
+                entity::get_component(track_id, replay_positions()),
+                entity::get_component(track_id, replay_rotations()),
+            ) else {
+                continue;
+            };
+
+            self.tracks.insert(
+                track_id,
+                ReplayTrack {
+                    subject_id,
+                    track: Track {
+                        interval,
+                        positions,
+                        rotations,
+                    },
+                    is_vehicle: entity::has_component(track_id, replay_is_vehicle()),
+                    finished: entity::has_component(track_id, replay_finished()),
+                    car_id: None,
+                },
+            );
+        }
+    }
+}
+
+struct ReplayTrack {
+    subject_id: EntityId,
+    track: Track,
+    is_vehicle: bool,
+    finished: bool,
+    /// The stand-in for the vehicle, spawned on this client only.
+    car_id: Option<EntityId>,
+}
+impl ReplayTrack {
+    fn show(&mut self, time: f32) {
+        let Some((position, rot)) = self.track.sample(time) else {
+            return;
+        };
+
+        if !self.is_vehicle {
+            // Animated objects are frozen on the server during the scoreboard,
+            // so they can be moved locally without being overwritten.
+            entity::set_component(self.subject_id, translation(), position);
+            entity::set_component(self.subject_id, rotation(), rot);
+            return;
+        }
+
+        let ended = time > self.track.duration();
+        if ended && self.finished {
+            if let Some(car_id) = self.car_id.take() {
+                entity::despawn(car_id);
+            }
+            return;
+        }
+
+        let car_id = *self.car_id.get_or_insert_with(|| {
+            Entity::new()
+                .with(cube(), ())
+                .with(scale(), vec3(1.2, 2.4, 0.6))
+                .with(color(), Vec4::ONE)
+                .spawn()
+        });
+
+        // Vehicles that died stay where they died, in red, until the replay loops
+        let car_color = if ended {
+            vec4(1.0, 0.1, 0.1, 1.0)
+        } else {
+            entity::get_component(self.subject_id, player_team())
+                .and_then(|team_id| entity::get_component(team_id, team_color()))
+                .unwrap_or(vec3(0.9, 0.9, 0.9))
+                .extend(1.0)
+        };
+
+        entity::set_component(car_id, translation(), position);
+        entity::set_component(car_id, rotation(), rot);
+        entity::set_component(car_id, color(), car_color);
+    }
+}
+
+#[element_component]
+pub fn ReplayControls(hooks: &mut Hooks) -> Element {
+    let speed = use_entity_component(hooks, entity::resources(), replay_speed()).unwrap_or(1.0);
+    let label = use_frame_value(hooks, || {
+        let time = entity::get_component(entity::resources(), replay_time()).unwrap_or_default();
+        let duration =
+            entity::get_component(entity::resources(), replay_duration()).unwrap_or_default();
+        format!(
+            "Replay: {} / {}",
+            format_race_time(Duration::from_secs_f32(time)),
+            format_race_time(Duration::from_secs_f32(duration))
+        )
+    });
+
+    let speed_button = |label: String, new_speed: f32| {
+        Button::new(label, move |_| {
+            entity::add_component(entity::resources(), replay_speed(), new_speed);
+        })
+        .style(if speed == new_speed {
+            ButtonStyle::Primary
+        } else {
+            ButtonStyle::Regular
+        })
+        .el()
+    };
+
+    FlowColumn::el([
+        Text::el(label),
+        FlowRow::el(
+            std::iter::once(speed_button("Pause".to_string(), 0.0)).chain(
+                SPEEDS
+                    .into_iter()
+                    .map(|speed| speed_button(format!("{speed}x"), speed)),
+            ),
+        )
+        .with(space_between_items(), 4.0),
+        Text::el(Controls::get().movement_help()),
+    ])
+    .with(space_between_items(), 4.0)
+}
@@ -0,0 +1,142 @@
+use ambient_api::{
+    core::{player::components::user_id, rendering::components::color},
+    element::use_query,
+    prelude::*,
+};
+
+use crate::{
+    format_race_time,
+    packages::{
+        tangent_rider_schema::{
+            components::spawnable_name,
+            types::{DeathCause, GamePhase},
+        },
+        this::{
+            components::{round_event_race_time, round_event_text, round_event_time},
+            messages::{ObjectPlaced, PhaseChanged, PlayerDied, PlayerFinished},
+        },
+    },
+    use_frame_value,
+};
+
+/// How long events stay in the kill feed, in seconds.
+const FEED_DURATION: f32 = 6.0;
+/// How long events take to fade out at the end of their time in the kill feed, in seconds.
+const FEED_FADE: f32 = 1.5;
+/// The most events shown in the kill feed at once.
+const FEED_LENGTH: usize = 6;
+
+/// Keeps a log of what happens during the round, starting from the construction phase.
+pub fn setup() {
+    PlayerDied::subscribe(|_ctx, msg| {
+        let name = player_name(msg.player_id);
+        let text = match msg.cause {
+            DeathCause::Water => format!("{name} fell into the water"),
+            DeathCause::Trap => format!("{name} was taken out by {}", player_name(msg.killer_id)),
+            DeathCause::Timeout => format!("{name} ran out of time"),
+            DeathCause::Respawn => format!("{name} gave up"),
+        };
+        log(text, Some(msg.race_time));
+    });
+
+    PlayerFinished::subscribe(|_ctx, msg| {
+        let text = format!(
+            "{} finished in {}",
+            player_name(msg.player_id),
+            format_race_time(msg.time)
+        );
+        log(text, Some(msg.time));
+    });
+
+    ObjectPlaced::subscribe(|_ctx, msg| {
+        let spawnable = entity::get_component(msg.spawnable_id, spawnable_name())
+            .unwrap_or("something".to_string());
+        log(
+            format!("{} placed {spawnable}", player_name(msg.player_id)),
+            None,
+        );
+    });
+
+    PhaseChanged::subscribe(|_ctx, msg| {
+        // A new round starts with construction, so forget the last one
+        if msg.phase == GamePhase::Construction {
+            for id in entity::get_all(round_event_text()) {
+                entity::despawn(id);
+            }
+        }
+
+        let text = match msg.phase {
+            GamePhase::Construction => "Construction started",
+            GamePhase::Play => "The race is on!",
+            GamePhase::Scoreboard => "The round is over",
+        };
+        log(text.to_string(), None);
+    });
+}
+
+/// Adds an event to the log. Events during the race are stamped with the server's race time, so
+/// that they line up for every player.
+fn log(text: String, race_time: Option<Duration>) {
+    let mut event = Entity::new()
+        .with(round_event_text(), text)
+        .with(round_event_time(), game_time());
+    if let Some(race_time) = race_time {
+        event.set(round_event_race_time(), race_time);
+    }
+    event.spawn();
+}
+
+fn player_name(player_id: EntityId) -> String {
+    entity::get_component(player_id, user_id()).unwrap_or("Someone".to_string())
+}
+
+/// The most recent round events, fading out over time.
+#[element_component]
+pub fn KillFeed(hooks: &mut Hooks) -> Element {
+    let mut events = use_query(hooks, (round_event_text(), round_event_time()));
+    events.sort_by_key(|(_, (_, time))| std::cmp::Reverse(*time));
+
+    // Opacity is quantized to tenths, so that the feed only re-renders when it visibly changes
+    let entries = use_frame_value(hooks, move || {
+        let now = game_time();
+        events
+            .iter()
+            .filter_map(|(_, (text, time))| {
+                let remaining = FEED_DURATION - (now - *time).as_secs_f32();
+                let opacity = (remaining / FEED_FADE).clamp(0., 1.);
+                (opacity > 0.).then(|| (text.clone(), (opacity * 10.).round() as u8))
+            })
+            .take(FEED_LENGTH)
+            .collect::<Vec<_>>()
+    });
+
+    if entries.is_empty() {
+        return Element::new();
+    }
+
+    WindowSized::el([FlowColumn::el(entries.into_iter().map(|(text, opacity)| {
+        let opacity = opacity as f32 / 10.;
+        with_rect(Text::el(text).with(color(), vec4(1.0, 1.0, 1.0, opacity)))
+            .with_padding_even(4.0)
+            .with_background(vec4(0.0, 0.0, 0.0, 0.5 * opacity))
+    }))
+    .with(space_between_items(), 4.0)
+    .with_padding_even(STREET)])
+}
+
+/// Everything that happened this round, with times relative to the start of the race.
+#[element_component]
+pub fn RoundLog(hooks: &mut Hooks) -> Element {
+    let mut events = use_query(hooks, (round_event_text(), round_event_time()));
+    events.sort_by_key(|(_, (_, time))| *time);
+
+    FlowColumn::el(
+        std::iter::once(Text::el("Round log").section_style()).chain(events.into_iter().map(
+            |(id, (text, _))| match entity::get_component(id, round_event_race_time()) {
+                Some(race_time) => Text::el(format!("{} {text}", format_race_time(race_time))),
+                None => Text::el(text),
+            },
+        )),
+    )
+    .with(space_between_items(), 2.0)
+}
@@ -1,8 +1,10 @@
 use ambient_api::{
     core::{
         app::components::main_scene,
-        messages::Collision,
-        physics::components::{cube_collider, dynamic, physics_controlled, plane_collider},
+        messages::{Collision, Frame},
+        physics::components::{
+            cube_collider, dynamic, kinematic, physics_controlled, plane_collider,
+        },
         player::components::is_player,
         primitives::components::{cube, quad},
         rendering::components::{color, fog_density, light_diffuse, sky, sun},
@@ -16,13 +18,20 @@ use packages::{
     game_object::components::health,
     tangent_rider_schema::{
         components::{
-            active_players, alive_player_queue, autospinner, game_phase, is_end_platform,
-            is_spawned, is_start_platform, player_construction_mode, player_current_spawnable,
-            player_current_spawnable_ghost, player_deaths, player_is_ready, player_money,
-            start_position, winner,
+            active_players, alive_player_queue, crumble_delay, game_phase, is_end_platform,
+            is_spawned, is_start_platform, linked_objects, placed_position, placed_rotation,
+            play_start_time, player_construction_mode, player_current_spawnable,
+            player_current_spawnable_ghost, player_deaths, player_finish_time,
+            player_income_amounts, player_income_sources, player_is_ready, player_money,
+            player_new_level_record, player_placed_parts, player_score, player_trap_kills,
+            player_vehicle_def, server_time, spawnable_part_ghost_refs, spawnable_part_main_refs,
+            spawned_by, spawned_from, start_position, surface_material, team_color, team_members,
+            team_mode, trigger_box, trigger_sphere, winner, winning_team,
         },
         concepts::Spawnable,
+        messages::TriggerEnter,
         types::ConstructionMode,
+        types::DeathCause,
         types::GamePhase,
     },
     tangent_schema::{
@@ -33,14 +42,36 @@ use packages::{
         },
     },
     tangent_spawner_vehicle::messages::VehicleSpawn,
-    this::messages::{
-        ConstructionCancel, ConstructionRotateGhost, ConstructionSetGhostPosition,
-        ConstructionSetMode, ConstructionSpawn, ConstructionSpawnGhost, Input, MarkAsReady,
+    this::{
+        components::{is_team_slot_marker, vehicle_last_trap_owner, vehicle_last_trap_time},
+        messages::{
+            ConstructionCancel, ConstructionRotateGhost, ConstructionSetGhostPosition,
+            ConstructionSetMode, ConstructionSpawn, ConstructionSpawnGhost, Input, MarkAsReady,
+            ObjectPlaced, PhaseChanged, PlayerDied, PlayerFinished, SelectVehicle, ToggleTeamMode,
+        },
     },
 };
 
+mod animation;
+mod ghost_run;
+mod income;
+mod input_settings;
+mod leaderboard;
+mod oscillator;
+mod recording;
+mod replay;
+mod storage;
+mod surface;
+mod teams;
+mod track;
+mod trigger;
+mod vanishing;
+
 #[main]
 pub async fn main() {
+    // Check that leaderboards, ghost runs and input settings can be saved before starting.
+    storage::setup();
+
     // Create the ground.
     let ground_id = Entity::new()
         .with(quad(), ())
@@ -63,6 +94,11 @@ pub async fn main() {
         .with(fog_density(), 0.)
         .spawn();
 
+    // Publish the server's clock, so that clients can tell how long is left before server times.
+    Frame::subscribe(|_| {
+        entity::add_component(entity::synchronized_resources(), server_time(), game_time());
+    });
+
     // When a vehicle spawns, add the vehicle to the player.
     spawn_query(vc::driver_ref())
         .requires(vc::is_vehicle())
@@ -101,18 +137,17 @@ pub async fn main() {
             }
         });
 
-    // Handle autospinners.
-    query(autospinner()).each_frame(|spinners| {
-        for (spinner_id, spinner_amount) in spinners {
-            entity::mutate_component(spinner_id, rotation(), |rot| {
-                let dt = delta_time();
-                *rot = Quat::from_rotation_z(spinner_amount.x * dt)
-                    * Quat::from_rotation_x(spinner_amount.y * dt)
-                    * Quat::from_rotation_y(spinner_amount.z * dt)
-                    * *rot;
-            });
-        }
-    });
+    // Animate spinning, moving and swinging objects.
+    animation::setup();
+
+    // Record vehicles and animated or vanishing objects during the race, keep the recordings for
+    // the round replay, and replay the best run on the level as a ghost.
+    recording::setup();
+    replay::setup();
+    ghost_run::setup();
+
+    // Keep each player's input settings between sessions.
+    input_settings::setup();
 
     // When a player sends input, update their input state.
     Input::subscribe(|ctx, input| {
@@ -193,14 +228,11 @@ pub async fn main() {
             return;
         };
 
-        let Some(ghost_id) = entity::get_component(player_id, player_current_spawnable_ghost())
-        else {
+        if !entity::has_component(player_id, player_current_spawnable_ghost()) {
             return;
-        };
+        }
 
-        entity::despawn(ghost_id);
-        entity::remove_component(player_id, player_current_spawnable());
-        entity::remove_component(player_id, player_current_spawnable_ghost());
+        remove_player_spawnable(player_id);
     });
 
     // Convert the ghost to a spawned object when requested.
@@ -229,25 +261,76 @@ pub async fn main() {
             return;
         };
 
-        if entity::mutate_component(player_id, player_money(), |money| {
-            *money = money.saturating_sub(spawnable.spawnable_cost)
-        })
-        .is_none()
-        {
+        // Spawnables with several parts are placed one part at a time, and paid for up front
+        let mut parts = entity::get_component(player_id, player_placed_parts()).unwrap_or_default();
+        let main_ref = if parts.is_empty() {
+            spawnable.spawnable_main_ref
+        } else {
+            let Some(main_ref) = entity::get_component(spawnable_id, spawnable_part_main_refs())
+                .and_then(|refs| refs.get(parts.len() - 1).copied())
+            else {
+                return;
+            };
+            main_ref
+        };
+        if parts.is_empty() && !teams::spend(player_id, spawnable.spawnable_cost) {
             return;
         }
 
         let Some(ghost) = entity::despawn(ghost_id) else {
             return;
         };
-        entity::remove_component(player_id, player_current_spawnable());
         entity::remove_component(player_id, player_current_spawnable_ghost());
 
-        entity::get_all_components(spawnable.spawnable_main_ref)
-            .with(translation(), ghost.get(translation()).unwrap_or_default())
-            .with(rotation(), ghost.get(rotation()).unwrap_or_default())
+        let position = ghost.get(translation()).unwrap_or_default();
+        let rot = ghost.get(rotation()).unwrap_or_default();
+        let mut object = entity::get_all_components(main_ref)
+            .with(translation(), position)
+            .with(rotation(), rot)
+            .with(placed_position(), position)
+            .with(placed_rotation(), rot)
             .with(is_spawned(), ())
-            .spawn();
+            .with(spawned_by(), player_id)
+            .with(spawned_from(), spawnable_id);
+        // Animated objects are driven as kinematic bodies, so that they push vehicles around
+        if animation::is_animated(main_ref) {
+            object = object.with(dynamic(), true).with(kinematic(), ());
+        }
+        // Objects that react to vehicles driving on them get a trigger just around them
+        if entity::has_component(main_ref, surface_material())
+            || entity::has_component(main_ref, crumble_delay())
+        {
+            object = object.with(trigger_box(), trigger::touch_box(main_ref));
+        }
+        parts.push(object.spawn());
+
+        // Ask for the next part, starting from where the last one was placed
+        if let Some(next_ghost_ref) =
+            entity::get_component(spawnable_id, spawnable_part_ghost_refs())
+                .and_then(|refs| refs.get(parts.len() - 1).copied())
+        {
+            let ghost_id = entity::get_all_components(next_ghost_ref)
+                .with(translation(), position)
+                .with(rotation(), rot)
+                .spawn();
+            entity::add_component(player_id, player_current_spawnable_ghost(), ghost_id);
+            entity::add_component(player_id, player_placed_parts(), parts);
+            return;
+        }
+
+        if parts.len() > 1 {
+            for id in &parts {
+                entity::add_component(*id, linked_objects(), parts.clone());
+            }
+        }
+        entity::remove_component(player_id, player_current_spawnable());
+        entity::remove_component(player_id, player_placed_parts());
+
+        ObjectPlaced {
+            player_id,
+            spawnable_id,
+        }
+        .send_client_broadcast_reliable();
     });
 
     // Handle construction set mode.
@@ -265,6 +348,39 @@ pub async fn main() {
         entity::add_component(player_id, player_construction_mode(), msg.mode);
     });
 
+    // Toggle team mode for the next game when requested during construction.
+    ToggleTeamMode::subscribe(|_ctx, _msg| {
+        let resources = entity::synchronized_resources();
+        if entity::get_component(resources, game_phase()) != Some(GamePhase::Construction) {
+            return;
+        }
+
+        if entity::has_component(resources, team_mode()) {
+            entity::remove_component(resources, team_mode());
+        } else {
+            entity::add_component(resources, team_mode(), ());
+        }
+    });
+
+    // Remember the player's choice of vehicle for the next play phase.
+    SelectVehicle::subscribe(|ctx, msg| {
+        let Some(player_id) = ctx.client_entity_id() else {
+            return;
+        };
+
+        if entity::get_component(entity::synchronized_resources(), game_phase())
+            != Some(GamePhase::Construction)
+        {
+            return;
+        }
+
+        if entity::has_component(msg.def_id, is_def()) {
+            entity::add_component(player_id, player_vehicle_def(), msg.def_id);
+        } else {
+            entity::remove_component(player_id, player_vehicle_def());
+        }
+    });
+
     // Mark the player as ready when requested.
     MarkAsReady::subscribe(|ctx, _| {
         if let Some(player_id) = ctx.client_entity_id() {
@@ -272,7 +388,9 @@ pub async fn main() {
         }
     });
 
-    // Sync player input state to vehicle input state.
+    // Sync player input state to vehicle input state. Input is ignored until the start
+    // countdown is over, so that vehicles stay frozen on the start platform. Respawning
+    // destroys the vehicle, letting a stuck player give up on the round.
     query((
         pc::input_direction(),
         pc::input_jump(),
@@ -280,11 +398,26 @@ pub async fn main() {
         pc::vehicle_ref(),
     ))
     .each_frame(|players| {
-        for (_player_id, (input_direction, input_jump, _input_respawn, vehicle_id)) in players {
+        let race_started =
+            entity::get_component(entity::synchronized_resources(), play_start_time())
+                .map_or(true, |start_time| game_time() >= start_time);
+
+        for (_player_id, (input_direction, input_jump, input_respawn, vehicle_id)) in players {
             if !entity::exists(vehicle_id) {
                 continue;
             }
 
+            if input_respawn && race_started {
+                kill_vehicle(vehicle_id, DeathCause::Respawn);
+                continue;
+            }
+
+            let (input_direction, input_jump) = if race_started {
+                (input_direction, input_jump)
+            } else {
+                (Vec2::ZERO, false)
+            };
+
             entity::add_components(
                 vehicle_id,
                 Entity::new()
@@ -294,41 +427,56 @@ pub async fn main() {
         }
     });
 
-    // If any vehicles collide with the water, blow them up.
+    // If any vehicles collide with the water, blow them up. If they collide with a spawned
+    // object instead, remember who spawned it so that they can be credited with the kill.
     Collision::subscribe(move |msg| {
-        if !msg.ids.contains(&ground_id) {
-            return;
-        }
-
-        for vehicle_id in msg
+        let vehicle_ids = msg
             .ids
             .iter()
             .copied()
-            .filter(|id| entity::has_component(*id, vc::is_vehicle()))
+            .filter(|id| entity::has_component(*id, vc::is_vehicle()));
+
+        if msg.ids.contains(&ground_id) {
+            for vehicle_id in vehicle_ids {
+                kill_vehicle(vehicle_id, DeathCause::Water);
+            }
+        } else if let Some(owner_id) = msg
+            .ids
+            .iter()
+            .find_map(|id| entity::get_component(*id, spawned_by()))
         {
-            entity::set_component(vehicle_id, health(), 0.);
+            for vehicle_id in vehicle_ids {
+                entity::add_components(
+                    vehicle_id,
+                    Entity::new()
+                        .with(vehicle_last_trap_owner(), owner_id)
+                        .with(vehicle_last_trap_time(), game_time()),
+                );
+            }
         }
     });
 
+    // Send trigger messages as vehicles move through trigger volumes.
+    trigger::setup();
+
+    // Apply surface materials to the vehicles driving over them.
+    surface::setup();
+
+    // Make crumbling and timed objects come and go.
+    vanishing::setup();
+
     // Handle reaching the end platform.
-    let end_platforms_query = query(translation()).requires(is_end_platform()).build();
-    query((translation(), vc::driver_ref()))
-        .requires(vc::is_vehicle())
-        .each_frame(move |vehicles| {
-            let end_platforms = end_platforms_query.evaluate();
+    TriggerEnter::subscribe(|_ctx, msg| {
+        if !entity::has_component(msg.trigger_id, is_end_platform()) {
+            return;
+        }
+        let Some(driver_id) = entity::get_component(msg.vehicle_id, vc::driver_ref()) else {
+            return;
+        };
 
-            for (vehicle_id, (position, driver_id)) in vehicles {
-                if end_platforms
-                    .iter()
-                    .any(|(_platform_id, platform_position)| {
-                        platform_position.distance_squared(position) < PLATFORM_WIDTH.powi(2)
-                    })
-                {
-                    entity::set_component(vehicle_id, health(), 0.);
-                    entity::add_component(entity::synchronized_resources(), winner(), driver_id);
-                }
-            }
-        });
+        entity::set_component(msg.vehicle_id, health(), 0.);
+        finish_race(driver_id);
+    });
 
     // Wait for vehicle defs to be available and for there to be at least one player, then start the game
     block_until(|| entity::get_all(is_def()).len() > 0 && entity::get_all(is_player()).len() > 0)
@@ -342,6 +490,20 @@ const PLATFORM_WIDTH: f32 = 6.0;
 /// The length of a single player slot on the platform in metres.
 const PLAYER_SLOT_LENGTH: f32 = 8.0;
 
+/// The longest the scoreboard (and round replay) is shown for.
+const SCOREBOARD_DURATION: Duration = Duration::from_secs(60);
+/// How long the start countdown lasts before the race begins.
+const START_COUNTDOWN: Duration = Duration::from_secs(3);
+/// How long a race can last before every remaining vehicle is destroyed.
+const PLAY_TIME_LIMIT: Duration = Duration::from_secs(180);
+
+/// The score awarded for reaching the end platform.
+const FINISH_SCORE: u32 = 3;
+/// The score awarded for killing another player with a spawned object.
+const TRAP_KILL_SCORE: u32 = 1;
+/// How long after touching a spawned object a death is still credited to its owner.
+const TRAP_KILL_WINDOW: Duration = Duration::from_secs(5);
+
 // "Production" settings
 /// The variance in angle of the end platform from the start platform.
 const LEVEL_ANGLE_VARIANCE: f32 = 45.0;
@@ -388,6 +550,30 @@ fn make_level() {
         .with(is_start_platform(), ())
         .spawn();
 
+    // Mark out each team's slots on the start platform
+    let mut slot_offset = 0.;
+    for team_id in teams::all_teams() {
+        let (Some(team_color), Some(members)) = (
+            entity::get_component(team_id, team_color()),
+            entity::get_component(team_id, team_members()),
+        ) else {
+            continue;
+        };
+
+        let team_length = PLAYER_SLOT_LENGTH * (members.len() as f32);
+        Entity::new()
+            .with(cube(), ())
+            .with(scale(), vec3(PLATFORM_WIDTH * 0.8, team_length - 1.0, 0.05))
+            .with(
+                translation(),
+                START_POSITION + vec3(0., slot_offset + team_length / 2., 0.1),
+            )
+            .with(color(), team_color.extend(1.0))
+            .with(is_team_slot_marker(), ())
+            .spawn();
+        slot_offset += team_length;
+    }
+
     let _end_platform = Entity::new()
         .with(cube(), ())
         .with(cube_collider(), Vec3::ONE)
@@ -395,6 +581,7 @@ fn make_level() {
         .with(translation(), end_position)
         .with(color(), vec4(0.0, 1.0, 0.0, 1.0))
         .with(is_end_platform(), ())
+        .with(trigger_sphere(), PLATFORM_WIDTH)
         .spawn();
 
     entity::add_component(
@@ -405,31 +592,32 @@ fn make_level() {
 }
 
 fn start_game() {
-    entity::add_component(
-        entity::synchronized_resources(),
-        active_players(),
-        entity::get_all(is_player()),
-    );
+    let players = entity::get_all(is_player());
+    teams::create_teams(&players);
+    entity::add_component(entity::synchronized_resources(), active_players(), players);
 
     make_level();
 
     start_construct_phase();
 }
 
+fn set_phase(phase: GamePhase) {
+    entity::add_component(entity::synchronized_resources(), game_phase(), phase);
+    PhaseChanged { phase }.send_client_broadcast_reliable();
+}
+
 fn start_construct_phase() {
-    entity::add_component(
-        entity::synchronized_resources(),
-        game_phase(),
-        GamePhase::Construction,
-    );
+    set_phase(GamePhase::Construction);
 
     // Prepare the entering-construction state for each player
     let players = entity::get_component(entity::synchronized_resources(), active_players())
         .unwrap_or_default();
 
+    ghost_run::despawn_all();
+    replay::clear();
+    income::pay_income(&players);
     for id in &players {
         entity::remove_component(*id, player_is_ready());
-        entity::mutate_component_with_default(*id, player_money(), 500, |money| *money += 500);
         entity::add_component(*id, player_construction_mode(), ConstructionMode::Place);
     }
 
@@ -450,10 +638,11 @@ fn start_construct_phase() {
 }
 
 fn start_play_phase() {
+    set_phase(GamePhase::Play);
     entity::add_component(
         entity::synchronized_resources(),
-        game_phase(),
-        GamePhase::Play,
+        play_start_time(),
+        game_time() + START_COUNTDOWN,
     );
 
     let mut active_players =
@@ -461,6 +650,16 @@ fn start_play_phase() {
             .unwrap_or_default();
     active_players.shuffle(&mut thread_rng());
 
+    // Group teammates together on the start platform, in the same order as the team markers
+    let team_order = teams::all_teams();
+    active_players.sort_by_key(|id| {
+        teams::team_of(*id).and_then(|team_id| team_order.iter().position(|id| *id == team_id))
+    });
+
+    leaderboard::start_level(&active_players);
+    ghost_run::spawn_best_run();
+    replay::start();
+
     let defs = entity::get_all(is_def());
     let start_position = entity::get_component(entity::synchronized_resources(), start_position())
         .unwrap_or_default();
@@ -473,13 +672,22 @@ fn start_play_phase() {
                 &pc::input_direction(),
                 &pc::input_jump(),
                 &pc::input_respawn(),
+                &player_finish_time(),
             ],
         );
+        entity::add_component(*player_id, player_trap_kills(), 0);
+
+        // Use the player's chosen vehicle if it's still available, or a random one otherwise
+        let def_id = entity::get_component(*player_id, player_vehicle_def())
+            .filter(|id| entity::has_component(*id, is_def()))
+            .unwrap_or_else(|| {
+                *defs
+                    .choose(&mut thread_rng())
+                    .expect("no defs available; this should not be possible")
+            });
 
         VehicleSpawn {
-            def_id: *defs
-                .choose(&mut thread_rng())
-                .expect("no defs available; this should not be possible"),
+            def_id,
             position: start_position + vec3(0., ((i as f32) + 0.5) * PLAYER_SLOT_LENGTH, 0.),
             rotation: Some(Quat::from_rotation_z(0f32.to_radians())),
             driver_id: Some(*player_id),
@@ -506,6 +714,12 @@ fn start_play_phase() {
                 // Everyone is dead without a winner, construct phase
                 start_construct_phase();
                 break;
+            } else if race_elapsed().is_some_and(|elapsed| elapsed > PLAY_TIME_LIMIT) {
+                // Out of time, so destroy everyone that's left
+                for vehicle_id in entity::get_all(is_vehicle()) {
+                    kill_vehicle(vehicle_id, DeathCause::Timeout);
+                }
+                sleep(0.1).await;
             } else {
                 // TODO: implement a yield() at some point
                 sleep(0.1).await;
@@ -515,24 +729,50 @@ fn start_play_phase() {
 }
 
 fn start_scoreboard_phase() {
-    entity::add_component(
-        entity::synchronized_resources(),
-        game_phase(),
-        GamePhase::Scoreboard,
-    );
+    set_phase(GamePhase::Scoreboard);
+    replay::finish();
+
+    // Give players time to watch the replay, or move on once everyone's ready
+    let players = entity::get_component(entity::synchronized_resources(), active_players())
+        .unwrap_or_default();
+    for id in &players {
+        entity::remove_component(*id, player_is_ready());
+    }
+    let end_time = game_time() + SCOREBOARD_DURATION;
 
     run_async(async move {
-        sleep(5.).await;
+        block_until(move || {
+            game_time() >= end_time
+                || players
+                    .iter()
+                    .all(|id| entity::has_component(*id, player_is_ready()))
+        })
+        .await;
 
+        // Scores start again with the next game, so that an early lead doesn't earn catch-up
+        // income for everyone else forever. Finish times and trap kills are kept, so that the
+        // next construction phase still pays out for this race.
         for id in entity::get_all(is_player()) {
-            entity::remove_components(id, &[&player_deaths(), &player_money()]);
+            entity::remove_components(
+                id,
+                &[
+                    &player_deaths(),
+                    &player_money(),
+                    &player_score(),
+                    &player_income_sources(),
+                    &player_income_amounts(),
+                ],
+            );
         }
 
         entity::remove_component(entity::synchronized_resources(), winner());
+        entity::remove_component(entity::synchronized_resources(), winning_team());
+        teams::destroy_teams();
 
         // Destroy the created level.
         for id in [
             entity::get_all(is_start_platform()),
+            entity::get_all(is_team_slot_marker()),
             entity::get_all(is_end_platform()),
             entity::get_all(is_spawned()),
             entity::get_all(is_vehicle()),
@@ -547,6 +787,17 @@ fn start_scoreboard_phase() {
     });
 }
 
+/// Returns how long the race has been running for, or `None` if it isn't running.
+fn race_elapsed() -> Option<Duration> {
+    let resources = entity::synchronized_resources();
+    if entity::get_component(resources, game_phase()) != Some(GamePhase::Play) {
+        return None;
+    }
+
+    let start_time = entity::get_component(resources, play_start_time())?;
+    game_time().checked_sub(start_time)
+}
+
 fn remove_player_spawnable(player_id: EntityId) {
     if let Some(existing_ghost_id) =
         entity::get_component(player_id, player_current_spawnable_ghost())
@@ -554,6 +805,104 @@ fn remove_player_spawnable(player_id: EntityId) {
         entity::despawn(existing_ghost_id);
     }
 
+    // Abandoning a spawnable partway through placing it takes back its parts and the money
+    if let Some(parts) = entity::get_component(player_id, player_placed_parts()) {
+        for id in parts {
+            entity::despawn(id);
+        }
+        if let Some(spawnable) = entity::get_component(player_id, player_current_spawnable())
+            .and_then(Spawnable::get_spawned)
+        {
+            teams::deposit(player_id, spawnable.spawnable_cost);
+        }
+        entity::remove_component(player_id, player_placed_parts());
+    }
+
     entity::remove_component(player_id, player_current_spawnable());
     entity::remove_component(player_id, player_current_spawnable_ghost());
 }
+
+/// Records the player's finish time, and makes them the winner if they were the first to finish.
+fn finish_race(player_id: EntityId) {
+    if entity::has_component(player_id, player_finish_time()) {
+        return;
+    }
+
+    let resources = entity::synchronized_resources();
+    let start_time = entity::get_component(resources, play_start_time()).unwrap_or_default();
+    let finish_time = game_time().saturating_sub(start_time);
+    entity::add_component(player_id, player_finish_time(), finish_time);
+    PlayerFinished {
+        player_id,
+        time: finish_time,
+    }
+    .send_client_broadcast_reliable();
+    leaderboard::record_finish(player_id, finish_time);
+    if entity::has_component(player_id, player_new_level_record()) {
+        if let Some(vehicle_id) = entity::get_component(player_id, pc::vehicle_ref()) {
+            ghost_run::save_run(vehicle_id);
+        }
+    }
+    entity::mutate_component_with_default(player_id, player_score(), FINISH_SCORE, |score| {
+        *score += FINISH_SCORE
+    });
+
+    if entity::has_component(resources, winner()) {
+        return;
+    }
+
+    entity::add_component(resources, winner(), player_id);
+    if let Some(team_id) = teams::team_of(player_id) {
+        entity::add_component(resources, winning_team(), team_id);
+    }
+}
+
+/// Destroys the vehicle and tells everyone how it died. Falling into the water is credited to
+/// the owner of the last spawned object the vehicle touched, if there is one.
+fn kill_vehicle(vehicle_id: EntityId, cause: DeathCause) {
+    if entity::get_component(vehicle_id, health()).unwrap_or_default() <= 0. {
+        return;
+    }
+
+    let killer_id = match cause {
+        DeathCause::Water => credit_trap_kill(vehicle_id),
+        _ => None,
+    };
+    entity::set_component(vehicle_id, health(), 0.);
+
+    if let Some(player_id) = entity::get_component(vehicle_id, vc::driver_ref()) {
+        PlayerDied {
+            player_id,
+            cause: if killer_id.is_some() {
+                DeathCause::Trap
+            } else {
+                cause
+            },
+            killer_id: killer_id.unwrap_or(EntityId::null()),
+            race_time: race_elapsed().unwrap_or_default(),
+        }
+        .send_client_broadcast_reliable();
+    }
+}
+
+/// Credits the owner of the last spawned object this vehicle touched with killing it,
+/// as long as it was touched recently and they aren't the vehicle's driver. Returns the
+/// credited player.
+fn credit_trap_kill(vehicle_id: EntityId) -> Option<EntityId> {
+    let driver_id = entity::get_component(vehicle_id, vc::driver_ref())?;
+    let owner_id = entity::get_component(vehicle_id, vehicle_last_trap_owner())?;
+    let touch_time = entity::get_component(vehicle_id, vehicle_last_trap_time())?;
+
+    if owner_id == driver_id
+        || !entity::exists(owner_id)
+        || game_time() - touch_time > TRAP_KILL_WINDOW
+    {
+        return None;
+    }
+
+    entity::mutate_component_with_default(owner_id, player_trap_kills(), 1, |kills| *kills += 1);
+    entity::mutate_component_with_default(owner_id, player_score(), TRAP_KILL_SCORE, |score| {
+        *score += TRAP_KILL_SCORE
+    });
+    Some(owner_id)
+}
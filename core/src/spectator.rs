@@ -0,0 +1,133 @@
+use ambient_api::{
+    core::{player::components::user_id, transform::components::translation},
+    element::use_entity_component,
+    prelude::*,
+};
+
+use crate::{
+    controls::{use_controls, Action, Controls},
+    packages::{
+        tangent_rider_schema::components::{alive_player_queue, is_end_platform},
+        tangent_schema::player::components as pc,
+        this::components::{spectator_auto_director, spectator_target},
+    },
+    vehicle_camera::VehicleCamera,
+    FlyCamera,
+};
+
+/// Follows the players that are still alive, for late joiners and players whose vehicle died.
+/// Falls back to a free camera when nobody is left to follow.
+#[derive(Default)]
+pub struct Spectator {
+    fly_camera: FlyCamera,
+    vehicle_camera: VehicleCamera,
+}
+impl Spectator {
+    pub fn tick(&mut self, camera_id: EntityId) {
+        let resources = entity::resources();
+        let (delta, input) = input::get_delta();
+        let controls = Controls::get();
+
+        let queue: Vec<EntityId> =
+            entity::get_component(entity::synchronized_resources(), alive_player_queue())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|id| *id != player::get_local())
+                .collect();
+        if queue.is_empty() {
+            entity::remove_component(resources, spectator_target());
+            return self.fly_camera.tick(camera_id, &delta, &input, true);
+        }
+
+        if controls.was_released(Action::ToggleAutoDirector, &delta) {
+            if entity::has_component(resources, spectator_auto_director()) {
+                entity::remove_component(resources, spectator_auto_director());
+            } else {
+                entity::add_component(resources, spectator_auto_director(), ());
+            }
+        }
+
+        // Keep following the same player if they're still alive, even if the queue changed
+        let current_index = entity::get_component(resources, spectator_target())
+            .and_then(|target_id| queue.iter().position(|id| *id == target_id))
+            .unwrap_or(0);
+        let offset = if controls.was_released(Action::SpectatePrevious, &delta) {
+            Some(queue.len() - 1)
+        } else if controls.was_released(Action::SpectateNext, &delta) {
+            Some(1)
+        } else {
+            None
+        };
+
+        let target_id = match offset {
+            Some(offset) => {
+                // Choosing a player by hand turns the director off
+                entity::remove_component(resources, spectator_auto_director());
+                queue[(current_index + offset) % queue.len()]
+            }
+            None if entity::has_component(resources, spectator_auto_director()) => {
+                closest_to_end(&queue).unwrap_or(queue[current_index])
+            }
+            None => queue[current_index],
+        };
+        entity::add_component(resources, spectator_target(), target_id);
+
+        if let Some(vehicle_id) = entity::get_component(target_id, pc::vehicle_ref()) {
+            let _ = self
+                .vehicle_camera
+                .tick(camera_id, vehicle_id, &delta, &input);
+        }
+    }
+}
+
+/// Returns the player whose vehicle is closest to the end platform.
+fn closest_to_end(players: &[EntityId]) -> Option<EntityId> {
+    let end_position = entity::get_all(is_end_platform())
+        .into_iter()
+        .find_map(|id| entity::get_component(id, translation()))?;
+
+    players
+        .iter()
+        .filter_map(|&player_id| {
+            let vehicle_id = entity::get_component(player_id, pc::vehicle_ref())?;
+            let position = entity::get_component(vehicle_id, translation())?;
+            Some((player_id, position.distance(end_position)))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(player_id, _)| player_id)
+}
+
+#[element_component]
+pub fn SpectatorLabel(hooks: &mut Hooks) -> Element {
+    let target_id = use_entity_component(hooks, entity::resources(), spectator_target());
+    let auto_director =
+        use_entity_component(hooks, entity::resources(), spectator_auto_director()).is_some();
+    let target_name =
+        use_entity_component(hooks, target_id.unwrap_or(player::get_local()), user_id());
+    let controls = use_controls(hooks);
+
+    let (Some(_), Some(target_name)) = (target_id, target_name) else {
+        return Element::new();
+    };
+
+    WindowSized::el([FlowColumn::el([with_rect(
+        FlowColumn::el([
+            Text::el(format!("Spectating {target_name}")).section_style(),
+            Text::el(format!(
+                "{}/{} to switch players, {} to toggle the auto-director ({})",
+                controls.label(Action::SpectatePrevious),
+                controls.label(Action::SpectateNext),
+                controls.label(Action::ToggleAutoDirector),
+                if auto_director { "on" } else { "off" }
+            )),
+        ])
+        .with(space_between_items(), 4.0),
+    )
+    .with_padding_even(8.0)
+    .with_background(vec4(0.0, 0.0, 0.0, 0.5))])
+    .with(fit_horizontal(), Fit::Parent)
+    .with(fit_vertical(), Fit::Parent)
+    .with(align_horizontal(), Align::Center)
+    .with(align_vertical(), Align::End)
+    .with_padding_even(STREET)])
+}
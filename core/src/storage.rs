@@ -0,0 +1,114 @@
+use ambient_api::prelude::*;
+
+use crate::packages::this::components::storage_error;
+
+/// Where everything the server keeps between sessions is saved. Ambient gives server modules a
+/// `data` directory that they can read and write with `std::fs`.
+const DATA_DIR: &str = "data";
+
+/// Checks that the server can save to the data directory. Leaderboards, ghost runs and input
+/// settings are all lost without it, so the server refuses to start rather than silently
+/// forgetting them.
+pub fn setup() {
+    let probe = path(".write_test");
+    let result = std::fs::create_dir_all(DATA_DIR)
+        .and_then(|_| std::fs::write(&probe, []))
+        .and_then(|_| std::fs::remove_file(&probe));
+    if let Err(err) = result {
+        panic!("The server can't save to its {DATA_DIR} directory: {err}");
+    }
+}
+
+/// Reads a file from the data directory. Returns `None` if it hasn't been saved yet.
+pub fn read(name: &str) -> Option<Vec<u8>> {
+    std::fs::read(path(name)).ok()
+}
+
+/// Reads a text file from the data directory. Returns `None` if it hasn't been saved yet.
+pub fn read_to_string(name: &str) -> Option<String> {
+    std::fs::read_to_string(path(name)).ok()
+}
+
+/// Writes a file to the data directory, creating any directories it's in. Failures are shown to
+/// every player, as whatever was being saved will be lost when the server stops.
+pub fn write(name: &str, contents: impl AsRef<[u8]>) {
+    let path = path(name);
+    let result = std::path::Path::new(&path)
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, contents));
+    if let Err(err) = result {
+        entity::add_component(
+            entity::synchronized_resources(),
+            storage_error(),
+            format!("Failed to save {name}: {err}"),
+        );
+    }
+}
+
+fn path(name: &str) -> String {
+    format!("{DATA_DIR}/{name}")
+}
+
+/// Escapes tabs, newlines and backslashes, so that any text can be stored as a field of a
+/// tab-separated line.
+pub fn escape_field(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses [escape_field]. Returns `None` if the field contains an invalid escape.
+pub fn unescape_field(field: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_round_trip() {
+        for field in [
+            "",
+            "plain",
+            "tab\tnew\nline",
+            "back\\slash",
+            "\\t",
+            "\r\n\\",
+        ] {
+            let escaped = escape_field(field);
+            assert!(!escaped.contains(['\t', '\n', '\r']), "{escaped:?}");
+            assert_eq!(unescape_field(&escaped).as_deref(), Some(field));
+        }
+    }
+
+    #[test]
+    fn unescape_rejects_invalid_escapes() {
+        assert_eq!(unescape_field("trailing\\"), None);
+        assert_eq!(unescape_field("\\x"), None);
+    }
+}
@@ -0,0 +1,159 @@
+use ambient_api::{
+    core::{
+        physics::components::{linear_velocity, mass},
+        transform::components::{rotation, translation},
+    },
+    prelude::*,
+};
+
+use crate::packages::{
+    tangent_rider_schema::{
+        components::{is_spawned, surface_material},
+        messages::{TriggerEnter, TriggerExit, TriggerStay},
+        types::SurfaceMaterial,
+    },
+    this::components::vehicle_surface_velocity,
+};
+
+/// How strongly changes to a vehicle's velocity along an icy surface are resisted, from 0 to 1.
+const ICE_SLIPPERINESS: f32 = 0.8;
+/// The fraction of its approach speed that a vehicle keeps when it bounces off a bouncy surface.
+const BOUNCE_RESTITUTION: f32 = 0.9;
+/// The slowest a vehicle leaves a bouncy surface, in metres/s, so that driving onto one flat
+/// still bounces.
+const MIN_BOUNCE_SPEED: f32 = 12.0;
+/// How quickly a sticky surface slows vehicles down. Higher is stickier.
+const STICKY_DRAG: f32 = 3.0;
+
+/// Applies the effects of surface materials to the vehicles driving over them.
+pub fn setup() {
+    TriggerEnter::subscribe(|_ctx, msg| {
+        let Some(material) = material_of(msg.trigger_id) else {
+            return;
+        };
+
+        match material {
+            SurfaceMaterial::Bouncy => bounce(msg.trigger_id, msg.vehicle_id),
+            SurfaceMaterial::Ice => {
+                if let Some(velocity) = entity::get_component(msg.vehicle_id, linear_velocity()) {
+                    entity::add_component(msg.vehicle_id, vehicle_surface_velocity(), velocity);
+                }
+            }
+            SurfaceMaterial::Sticky => {}
+        }
+    });
+
+    TriggerStay::subscribe(|_ctx, msg| {
+        let Some(material) = material_of(msg.trigger_id) else {
+            return;
+        };
+        let (Some(velocity), Some(vehicle_mass)) = (
+            entity::get_component(msg.vehicle_id, linear_velocity()),
+            entity::get_component(msg.vehicle_id, mass()),
+        ) else {
+            return;
+        };
+
+        match material {
+            SurfaceMaterial::Ice => {
+                let normal = normal_of(msg.trigger_id);
+                let last_velocity =
+                    entity::get_component(msg.vehicle_id, vehicle_surface_velocity())
+                        .unwrap_or(velocity);
+                entity::add_component(msg.vehicle_id, vehicle_surface_velocity(), velocity);
+
+                // Work against whatever the vehicle did to change its speed or direction along
+                // the surface, including braking and steering
+                let sideways_change = along_surface(velocity - last_velocity, normal);
+                let dt = delta_time().max(0.001);
+                physics::add_force(
+                    msg.vehicle_id,
+                    -sideways_change / dt * vehicle_mass * ICE_SLIPPERINESS,
+                );
+            }
+            SurfaceMaterial::Sticky => {
+                physics::add_force(msg.vehicle_id, -velocity * vehicle_mass * STICKY_DRAG);
+            }
+            SurfaceMaterial::Bouncy => {}
+        }
+    });
+
+    TriggerExit::subscribe(|_ctx, msg| {
+        if material_of(msg.trigger_id) == Some(SurfaceMaterial::Ice) {
+            entity::remove_component(msg.vehicle_id, vehicle_surface_velocity());
+        }
+    });
+}
+
+fn material_of(id: EntityId) -> Option<SurfaceMaterial> {
+    if !entity::has_component(id, is_spawned()) {
+        return None;
+    }
+    entity::get_component(id, surface_material())
+}
+
+/// Returns the direction that the object's top face points in.
+fn normal_of(id: EntityId) -> Vec3 {
+    entity::get_component(id, rotation()).unwrap_or_default() * Vec3::Z
+}
+
+fn bounce(surface_id: EntityId, vehicle_id: EntityId) {
+    let (Some(position), Some(velocity), Some(vehicle_mass)) = (
+        entity::get_component(vehicle_id, translation()),
+        entity::get_component(vehicle_id, linear_velocity()),
+        entity::get_component(vehicle_id, mass()),
+    ) else {
+        return;
+    };
+
+    // Bounce away from whichever face the vehicle is on
+    let surface_position = entity::get_component(surface_id, translation()).unwrap_or_default();
+    let mut normal = normal_of(surface_id);
+    if (position - surface_position).dot(normal) < 0. {
+        normal = -normal;
+    }
+
+    physics::add_impulse(vehicle_id, bounce_change(velocity, normal) * vehicle_mass);
+}
+
+/// Returns the part of the vector that lies along the surface with the given normal.
+fn along_surface(vector: Vec3, normal: Vec3) -> Vec3 {
+    vector - normal * vector.dot(normal)
+}
+
+/// Returns the change in velocity that bounces a vehicle off the face with the given normal.
+fn bounce_change(velocity: Vec3, normal: Vec3) -> Vec3 {
+    let approach_speed = (-velocity.dot(normal)).max(0.);
+    let leave_speed = (approach_speed * BOUNCE_RESTITUTION).max(MIN_BOUNCE_SPEED);
+    normal * (approach_speed + leave_speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn along_surface_drops_the_normal_part() {
+        let normal = vec3(0., 1., 1.).normalize();
+        let along = along_surface(vec3(3., 2., 0.), normal);
+        assert!(along.dot(normal).abs() < 1e-5);
+        assert!(along.abs_diff_eq(vec3(3., 1., -1.), 1e-5), "{along}");
+    }
+
+    #[test]
+    fn fast_bounce_keeps_most_of_its_speed() {
+        let velocity = vec3(5., 0., -20.);
+        let after = velocity + bounce_change(velocity, Vec3::Z);
+        assert!(after.abs_diff_eq(vec3(5., 0., 20. * BOUNCE_RESTITUTION), 1e-4));
+    }
+
+    #[test]
+    fn slow_bounce_leaves_at_minimum_speed() {
+        // Driving onto it flat, or even already leaving it
+        for velocity in [vec3(10., 0., 0.), vec3(0., 0., -1.), vec3(0., 0., 1.)] {
+            let after = velocity + bounce_change(velocity, Vec3::Z);
+            assert!(after.z >= MIN_BOUNCE_SPEED - 1e-4, "{after}");
+            assert_eq!(after.x, velocity.x);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use ambient_api::{
+    core::{
+        app::components::main_scene,
+        rect::components::{line_from, line_to, line_width},
+        rendering::components::{color, double_sided},
+        transform::components::{rotation, scale, translation},
+    },
+    element::{use_entity_component, use_query},
+    prelude::*,
+};
+
+use crate::{
+    oscillator::Oscillator,
+    packages::tangent_rider_schema::components::{
+        oscillator_angle, player_current_spawnable_ghost,
+    },
+};
+
+/// How many straight lines each swing arc is drawn with.
+const ARC_SEGMENTS: usize = 16;
+
+/// Draws the arc that each construction ghost will swing through once it's placed, so that
+/// swinging obstacles can be lined up before they're paid for.
+#[element_component]
+pub fn SwingPreviews(hooks: &mut Hooks) -> Element {
+    let ghosts = use_query(hooks, player_current_spawnable_ghost());
+    Group::el(
+        ghosts
+            .into_iter()
+            .filter(|(_, ghost_id)| entity::has_component(*ghost_id, oscillator_angle()))
+            .map(|(_, ghost_id)| SwingPreview::el(ghost_id)),
+    )
+}
+
+#[element_component]
+fn SwingPreview(hooks: &mut Hooks, ghost_id: EntityId) -> Element {
+    let position = use_entity_component(hooks, ghost_id, translation()).unwrap_or_default();
+    let rot = use_entity_component(hooks, ghost_id, rotation()).unwrap_or_default();
+    let size = use_entity_component(hooks, ghost_id, scale()).unwrap_or(Vec3::ONE);
+    let Some(oscillator) = Oscillator::get(ghost_id) else {
+        return Element::new();
+    };
+
+    // The end opposite the pivot sweeps the widest arc. Objects that swing around their centre
+    // are traced from the end of their longest side instead.
+    let reach = if oscillator.pivot.length() > 0.01 {
+        -oscillator.pivot
+    } else if size.x >= size.y && size.x >= size.z {
+        Vec3::X * size.x / 2.
+    } else if size.y >= size.z {
+        Vec3::Y * size.y / 2.
+    } else {
+        Vec3::Z * size.z / 2.
+    };
+    let tip_at = |extent: f32| {
+        let (offset, swing) = oscillator.swing(extent);
+        position + rot * (offset + swing * reach)
+    };
+
+    let pivot = position + rot * oscillator.pivot;
+    let arc: Vec<Vec3> = (0..=ARC_SEGMENTS)
+        .map(|i| tip_at(i as f32 / ARC_SEGMENTS as f32 * 2. - 1.))
+        .collect();
+
+    Group::el(
+        arc.windows(2)
+            .map(|segment| make_line(segment[0], segment[1]))
+            .chain([make_line(pivot, tip_at(-1.)), make_line(pivot, tip_at(1.))]),
+    )
+}
+
+fn make_line(p0: Vec3, p1: Vec3) -> Element {
+    Element::new()
+        .with(main_scene(), ())
+        .with(line_from(), p0)
+        .with(line_to(), p1)
+        .with(line_width(), 0.1)
+        .with(color(), vec4(1.0, 0.8, 0.2, 1.0))
+        .with(double_sided(), true)
+}
@@ -0,0 +1,141 @@
+use ambient_api::prelude::*;
+
+use crate::packages::tangent_rider_schema::{
+    components::{
+        is_team, player_money, player_team, team_members, team_mode, team_money, team_name,
+    },
+    concepts::Team,
+};
+
+/// How many players each team should have. Larger games are split into more teams, up to one
+/// for each entry in [TEAMS].
+const PLAYERS_PER_TEAM: usize = 2;
+/// The names and colors of each team, in the order they are created.
+const TEAMS: [(&str, Vec3); 4] = [
+    ("Red", vec3(0.9, 0.2, 0.2)),
+    ("Blue", vec3(0.2, 0.4, 0.9)),
+    ("Yellow", vec3(0.9, 0.8, 0.2)),
+    ("Purple", vec3(0.6, 0.3, 0.9)),
+];
+
+/// Splits the players into teams if team mode is on.
+pub fn create_teams(players: &[EntityId]) {
+    if !entity::has_component(entity::synchronized_resources(), team_mode()) || players.len() < 2 {
+        return;
+    }
+
+    let mut players = players.to_vec();
+    players.shuffle(&mut thread_rng());
+
+    let team_count = team_count(players.len());
+    for (team_index, (name, color)) in TEAMS.iter().take(team_count).enumerate() {
+        let members: Vec<EntityId> = players
+            .iter()
+            .skip(team_index)
+            .step_by(team_count)
+            .copied()
+            .collect();
+
+        let team_id = Team {
+            is_team: (),
+            team_name: name.to_string(),
+            team_color: *color,
+            team_members: members.clone(),
+            team_money: 0,
+        }
+        .spawn();
+
+        for player_id in members {
+            entity::add_component(player_id, player_team(), team_id);
+        }
+    }
+}
+
+/// Returns how many teams the players should be split into: one for every [PLAYERS_PER_TEAM]
+/// players, but always at least two.
+fn team_count(player_count: usize) -> usize {
+    (player_count / PLAYERS_PER_TEAM).clamp(2, TEAMS.len())
+}
+
+/// Removes all teams and the players' membership of them.
+pub fn destroy_teams() {
+    for (team_id, members) in query(team_members()).requires(is_team()).build().evaluate() {
+        for player_id in members {
+            entity::remove_component(player_id, player_team());
+        }
+        entity::despawn(team_id);
+    }
+}
+
+/// Returns all teams, in the order they were created.
+pub fn all_teams() -> Vec<EntityId> {
+    let mut teams = query(team_name()).requires(is_team()).build().evaluate();
+    teams.sort_by_key(|(_, name)| {
+        TEAMS
+            .iter()
+            .position(|(team_name, _)| *team_name == name.as_str())
+    });
+    teams.into_iter().map(|(id, _)| id).collect()
+}
+
+/// Returns the team of the player, if they are in one.
+pub fn team_of(player_id: EntityId) -> Option<EntityId> {
+    entity::get_component(player_id, player_team()).filter(|id| entity::exists(*id))
+}
+
+/// Returns the number of players that share the player's wallet.
+pub fn wallet_size(player_id: EntityId) -> usize {
+    team_of(player_id)
+        .and_then(|team_id| entity::get_component(team_id, team_members()))
+        .map(|members| members.len().max(1))
+        .unwrap_or(1)
+}
+
+/// Returns the money available to the player: their team's money in team mode, or their own.
+pub fn money(player_id: EntityId) -> u32 {
+    match team_of(player_id) {
+        Some(team_id) => entity::get_component(team_id, team_money()),
+        None => entity::get_component(player_id, player_money()),
+    }
+    .unwrap_or_default()
+}
+
+/// Adds money to the player's wallet.
+pub fn deposit(player_id: EntityId, amount: u32) {
+    let mutate = |money: &mut u32| *money += amount;
+    match team_of(player_id) {
+        Some(team_id) => {
+            entity::mutate_component_with_default(team_id, team_money(), amount, mutate)
+        }
+        None => entity::mutate_component_with_default(player_id, player_money(), amount, mutate),
+    }
+}
+
+/// Takes money from the player's wallet. Returns false if the player has no wallet.
+pub fn spend(player_id: EntityId, amount: u32) -> bool {
+    let mutate = |money: &mut u32| *money = money.saturating_sub(amount);
+    match team_of(player_id) {
+        Some(team_id) => entity::mutate_component(team_id, team_money(), mutate),
+        None => entity::mutate_component(player_id, player_money(), mutate),
+    }
+    .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn team_count_grows_with_players() {
+        assert_eq!(team_count(2), 2);
+        assert_eq!(team_count(3), 2);
+        assert_eq!(team_count(5), 2);
+        assert_eq!(team_count(6), 3);
+        assert_eq!(team_count(8), 4);
+    }
+
+    #[test]
+    fn team_count_is_limited_to_defined_teams() {
+        assert_eq!(team_count(100), TEAMS.len());
+    }
+}
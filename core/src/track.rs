@@ -0,0 +1,213 @@
+use ambient_api::prelude::*;
+
+/// Identifies the start of an encoded track, and its format version.
+#[cfg(feature = "server")]
+const MAGIC: &[u8; 4] = b"TRK1";
+
+/// A recording of an object's position and rotation, sampled at a fixed interval.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Track {
+    /// The time between samples, in seconds.
+    pub interval: f32,
+    pub positions: Vec<Vec3>,
+    pub rotations: Vec<Quat>,
+}
+impl Track {
+    pub fn len(&self) -> usize {
+        self.positions.len().min(self.rotations.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The length of the recording in seconds.
+    #[cfg(feature = "client")]
+    pub fn duration(&self) -> f32 {
+        self.len().saturating_sub(1) as f32 * self.interval
+    }
+
+    /// Returns the interpolated position and rotation at `time` seconds into the recording.
+    /// Times outside of the recording are clamped to its first or last sample.
+    pub fn sample(&self, time: f32) -> Option<(Vec3, Quat)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let t = (time / self.interval).clamp(0., (self.len() - 1) as f32);
+        let (i0, i1) = (t.floor() as usize, t.ceil() as usize);
+        let alpha = t.fract();
+
+        Some((
+            self.positions[i0].lerp(self.positions[i1], alpha),
+            self.rotations[i0].slerp(self.rotations[i1], alpha),
+        ))
+    }
+}
+
+// Tracks are only built and saved on the server, and the client only plays them back
+#[cfg(feature = "server")]
+impl Track {
+    pub fn new(interval: f32) -> Self {
+        Self {
+            interval,
+            ..default()
+        }
+    }
+
+    pub fn push(&mut self, position: Vec3, rotation: Quat) {
+        self.positions.push(position);
+        self.rotations.push(rotation);
+    }
+
+    /// Encodes the track into a compact binary format. Positions are stored at full precision,
+    /// while rotations are quantized to 16 bits per component.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + self.len() * 20);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.interval.to_le_bytes());
+        bytes.extend_from_slice(&(self.len() as u32).to_le_bytes());
+
+        for (position, rotation) in self.positions.iter().zip(&self.rotations) {
+            for value in position.to_array() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            for value in rotation.normalize().to_array() {
+                bytes.extend_from_slice(&((value * i16::MAX as f32).round() as i16).to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes a track produced by [Self::to_bytes]. Returns `None` if the data is not a valid track.
+    pub fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        if take::<4>(&mut bytes)? != *MAGIC {
+            return None;
+        }
+        let interval = f32::from_le_bytes(take(&mut bytes)?);
+        let len = u32::from_le_bytes(take(&mut bytes)?);
+
+        let mut track = Self::new(interval);
+        for _ in 0..len {
+            let mut position = [0f32; 3];
+            for value in &mut position {
+                *value = f32::from_le_bytes(take(&mut bytes)?);
+            }
+            let mut rotation = [0f32; 4];
+            for value in &mut rotation {
+                *value = i16::from_le_bytes(take(&mut bytes)?) as f32 / i16::MAX as f32;
+            }
+
+            track.push(
+                Vec3::from_array(position),
+                Quat::from_array(rotation).normalize(),
+            );
+        }
+
+        Some(track)
+    }
+}
+
+/// Splits the first `N` bytes off the front of `bytes`.
+#[cfg(feature = "server")]
+fn take<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    if bytes.len() < N {
+        return None;
+    }
+
+    let (head, rest) = bytes.split_at(N);
+    *bytes = rest;
+    head.try_into().ok()
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    fn test_track() -> Track {
+        let mut track = Track::new(0.5);
+        track.push(vec3(0., 0., 0.), Quat::IDENTITY);
+        track.push(vec3(2., -4., 1.5), Quat::from_rotation_z(1.0));
+        track.push(vec3(-3.25, 8., 100.), Quat::from_rotation_x(-2.5));
+        track
+    }
+
+    #[test]
+    fn round_trip() {
+        let track = test_track();
+        let decoded = Track::from_bytes(&track.to_bytes()).unwrap();
+
+        assert_eq!(decoded.interval, track.interval);
+        assert_eq!(decoded.positions, track.positions);
+        assert_eq!(decoded.rotations.len(), track.rotations.len());
+        for (decoded, original) in decoded.rotations.iter().zip(&track.rotations) {
+            assert!(
+                decoded.abs_diff_eq(*original, 1e-4),
+                "{decoded} != {original}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_quantizes_extreme_rotations() {
+        let mut track = Track::new(0.1);
+        // Components of exactly 1 and -1 hit the ends of the quantized range
+        track.push(Vec3::ZERO, Quat::from_xyzw(0., 0., 0., -1.));
+        track.push(Vec3::ZERO, Quat::from_xyzw(1., 0., 0., 0.));
+        // Unnormalized rotations are normalized before they're quantized
+        track.push(Vec3::ZERO, Quat::from_xyzw(0., 0., 2., 2.));
+
+        let decoded = Track::from_bytes(&track.to_bytes()).unwrap();
+        for (decoded, original) in decoded.rotations.iter().zip(&track.rotations) {
+            assert!(decoded.is_normalized());
+            assert!(decoded.abs_diff_eq(original.normalize(), 1e-4));
+        }
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let track = Track::new(0.25);
+        assert_eq!(Track::from_bytes(&track.to_bytes()), Some(track));
+    }
+
+    #[test]
+    fn decode_rejects_invalid_data() {
+        let bytes = test_track().to_bytes();
+        assert_eq!(Track::from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(Track::from_bytes(&bytes[..3]), None);
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[3] = b'2';
+        assert_eq!(Track::from_bytes(&wrong_magic), None);
+    }
+
+    #[test]
+    fn sample_at_keyframes() {
+        let track = test_track();
+        for (i, (position, rotation)) in track.positions.iter().zip(&track.rotations).enumerate() {
+            let (sampled_position, sampled_rotation) = track.sample(i as f32 * 0.5).unwrap();
+            assert_eq!(sampled_position, *position);
+            assert!(sampled_rotation.abs_diff_eq(*rotation, 1e-6));
+        }
+    }
+
+    #[test]
+    fn sample_between_keyframes() {
+        let track = test_track();
+        let (position, rotation) = track.sample(0.25).unwrap();
+        assert!(position.abs_diff_eq(vec3(1., -2., 0.75), 1e-6));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_z(0.5), 1e-6));
+
+        let (position, _) = track.sample(0.875).unwrap();
+        assert!(position.abs_diff_eq(vec3(2., -4., 1.5).lerp(vec3(-3.25, 8., 100.), 0.75), 1e-5));
+    }
+
+    #[test]
+    fn sample_clamps_outside_recording() {
+        let track = test_track();
+        assert_eq!(track.sample(-1.).unwrap().0, track.positions[0]);
+        assert_eq!(track.sample(10.).unwrap().0, track.positions[2]);
+        assert_eq!(Track::new(0.5).sample(0.), None);
+    }
+}
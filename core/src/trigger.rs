@@ -0,0 +1,296 @@
+use std::collections::{HashMap, HashSet};
+
+use ambient_api::{
+    core::transform::components::{rotation, scale, translation},
+    ecs::GeneralQuery,
+    prelude::*,
+};
+
+use crate::packages::{
+    tangent_rider_schema::{
+        components::{trigger_box, trigger_sphere},
+        messages::{TriggerEnter, TriggerExit, TriggerStay},
+    },
+    tangent_schema::vehicle::components::is_vehicle,
+};
+
+/// The size of each cell of the spatial index, in metres.
+const CELL_SIZE: f32 = 16.0;
+/// Triggers that would cover more cells than this are checked against every vehicle instead,
+/// so that one huge trigger doesn't fill the index.
+const MAX_CELLS_PER_TRIGGER: i32 = 64;
+
+/// How far above and below an object vehicles count as being on it, in metres. Vehicles hover,
+/// so they never actually touch the surfaces they drive on.
+const TOUCH_REACH: f32 = 1.5;
+
+/// Sends `TriggerEnter`, `TriggerStay` and `TriggerExit` as vehicles move through trigger volumes.
+pub fn setup() {
+    let box_query = query((translation(), trigger_box())).build();
+    let sphere_query = query((translation(), trigger_sphere())).build();
+
+    let mut inside = HashSet::<(EntityId, EntityId)>::new();
+    query(translation())
+        .requires(is_vehicle())
+        .each_frame(move |vehicles| {
+            // Triggers can move, so the index is rebuilt every frame
+            let index = TriggerIndex::build(&box_query, &sphere_query);
+
+            let mut now_inside = HashSet::new();
+            for (vehicle_id, position) in vehicles {
+                for trigger_id in index.containing(position) {
+                    now_inside.insert((trigger_id, vehicle_id));
+                }
+            }
+
+            for &(trigger_id, vehicle_id) in &now_inside {
+                if inside.contains(&(trigger_id, vehicle_id)) {
+                    TriggerStay {
+                        trigger_id,
+                        vehicle_id,
+                    }
+                    .send_local_broadcast(true);
+                } else {
+                    TriggerEnter {
+                        trigger_id,
+                        vehicle_id,
+                    }
+                    .send_local_broadcast(true);
+                }
+            }
+            // Vehicles that were destroyed inside a trigger also leave it
+            for &(trigger_id, vehicle_id) in inside.difference(&now_inside) {
+                TriggerExit {
+                    trigger_id,
+                    vehicle_id,
+                }
+                .send_local_broadcast(true);
+            }
+
+            inside = now_inside;
+        });
+}
+
+/// Returns the size of a trigger box that detects vehicles driving on the object.
+pub fn touch_box(id: EntityId) -> Vec3 {
+    entity::get_component(id, scale()).unwrap_or(Vec3::ONE) + Vec3::Z * TOUCH_REACH * 2.
+}
+
+enum Shape {
+    Box { half_extents: Vec3 },
+    Sphere { radius: f32 },
+}
+
+struct Trigger {
+    id: EntityId,
+    position: Vec3,
+    rotation: Quat,
+    shape: Shape,
+}
+impl Trigger {
+    fn contains(&self, point: Vec3) -> bool {
+        match self.shape {
+            Shape::Box { half_extents } => {
+                // Work in the trigger's space, where the box is axis-aligned
+                let local = self.rotation.inverse() * (point - self.position);
+                local.abs().cmple(half_extents).all()
+            }
+            Shape::Sphere { radius } => self.position.distance_squared(point) <= radius.powi(2),
+        }
+    }
+
+    /// Returns the radius of a sphere around the trigger's centre that contains all of it.
+    fn bounding_radius(&self) -> f32 {
+        match self.shape {
+            Shape::Box { half_extents } => half_extents.length(),
+            Shape::Sphere { radius } => radius,
+        }
+    }
+}
+
+/// A uniform grid of the triggers in the level, so that each vehicle is only tested against
+/// the triggers near it.
+struct TriggerIndex {
+    triggers: Vec<Trigger>,
+    cells: HashMap<IVec3, Vec<usize>>,
+    /// Triggers that are too large to be put in the grid.
+    large: Vec<usize>,
+}
+impl TriggerIndex {
+    fn build(
+        box_query: &GeneralQuery<(Component<Vec3>, Component<Vec3>)>,
+        sphere_query: &GeneralQuery<(Component<Vec3>, Component<f32>)>,
+    ) -> Self {
+        let boxes = box_query
+            .evaluate()
+            .into_iter()
+            .map(|(id, (position, size))| {
+                (
+                    id,
+                    position,
+                    Shape::Box {
+                        half_extents: size / 2.,
+                    },
+                )
+            });
+        let spheres = sphere_query
+            .evaluate()
+            .into_iter()
+            .map(|(id, (position, radius))| (id, position, Shape::Sphere { radius }));
+
+        Self::new(
+            boxes
+                .chain(spheres)
+                .map(|(id, position, shape)| Trigger {
+                    id,
+                    position,
+                    rotation: entity::get_component(id, rotation()).unwrap_or_default(),
+                    shape,
+                })
+                .collect(),
+        )
+    }
+
+    fn new(triggers: Vec<Trigger>) -> Self {
+        let mut cells = HashMap::<IVec3, Vec<usize>>::new();
+        let mut large = vec![];
+        for (trigger_index, trigger) in triggers.iter().enumerate() {
+            let reach = Vec3::splat(trigger.bounding_radius());
+            let min = cell_of(trigger.position - reach);
+            let max = cell_of(trigger.position + reach);
+            let span = max - min + IVec3::ONE;
+
+            if span.x * span.y * span.z > MAX_CELLS_PER_TRIGGER {
+                large.push(trigger_index);
+                continue;
+            }
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    for z in min.z..=max.z {
+                        cells.entry(ivec3(x, y, z)).or_default().push(trigger_index);
+                    }
+                }
+            }
+        }
+
+        Self {
+            triggers,
+            cells,
+            large,
+        }
+    }
+
+    /// Returns the triggers that contain the point.
+    fn containing(&self, point: Vec3) -> impl Iterator<Item = EntityId> + '_ {
+        self.cells
+            .get(&cell_of(point))
+            .into_iter()
+            .flatten()
+            .chain(&self.large)
+            .map(|index| &self.triggers[*index])
+            .filter(move |trigger| trigger.contains(point))
+            .map(|trigger| trigger.id)
+    }
+}
+
+fn cell_of(point: Vec3) -> IVec3 {
+    (point / CELL_SIZE).floor().as_ivec3()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    fn trigger(id: u64, position: Vec3, rotation: Quat, shape: Shape) -> Trigger {
+        Trigger {
+            id: EntityId { id0: id, id1: 0 },
+            position,
+            rotation,
+            shape,
+        }
+    }
+
+    #[test]
+    fn box_contains() {
+        let trigger = trigger(
+            1,
+            vec3(10., 0., 0.),
+            Quat::IDENTITY,
+            Shape::Box {
+                half_extents: vec3(1., 2., 3.),
+            },
+        );
+        assert!(trigger.contains(vec3(10., 0., 0.)));
+        assert!(trigger.contains(vec3(11., -2., 3.)));
+        assert!(!trigger.contains(vec3(11.1, 0., 0.)));
+        assert!(!trigger.contains(vec3(10., 0., -3.1)));
+    }
+
+    #[test]
+    fn rotated_box_contains() {
+        // A long thin box, turned 45 degrees around Z
+        let trigger = trigger(
+            1,
+            Vec3::ZERO,
+            Quat::from_rotation_z(FRAC_PI_4),
+            Shape::Box {
+                half_extents: vec3(4., 0.5, 0.5),
+            },
+        );
+        assert!(trigger.contains(vec3(2., 2., 0.)));
+        assert!(!trigger.contains(vec3(3., 0., 0.)));
+    }
+
+    #[test]
+    fn sphere_contains() {
+        let trigger = trigger(
+            1,
+            vec3(0., 0., 5.),
+            Quat::IDENTITY,
+            Shape::Sphere { radius: 2. },
+        );
+        assert!(trigger.contains(vec3(0., 2., 5.)));
+        assert!(!trigger.contains(vec3(1.5, 1.5, 5.)));
+        assert_eq!(trigger.bounding_radius(), 2.);
+    }
+
+    #[test]
+    fn index_finds_triggers_across_cells() {
+        // Straddles the cell boundary at the origin
+        let small = trigger(1, Vec3::ZERO, Quat::IDENTITY, Shape::Sphere { radius: 2. });
+        let far = trigger(
+            2,
+            Vec3::splat(100.),
+            Quat::IDENTITY,
+            Shape::Box {
+                half_extents: Vec3::ONE,
+            },
+        );
+        let index = TriggerIndex::new(vec![small, far]);
+        assert!(index.large.is_empty());
+
+        let ids = |point: Vec3| index.containing(point).map(|id| id.id0).collect::<Vec<_>>();
+        assert_eq!(ids(vec3(1., 1., 1.)), [1]);
+        assert_eq!(ids(vec3(-1., -1., -1.)), [1]);
+        assert_eq!(ids(vec3(100.5, 100., 99.5)), [2]);
+        assert!(ids(vec3(50., 50., 50.)).is_empty());
+    }
+
+    #[test]
+    fn index_keeps_large_triggers_out_of_the_grid() {
+        let huge = trigger(
+            1,
+            Vec3::ZERO,
+            Quat::IDENTITY,
+            Shape::Sphere {
+                radius: CELL_SIZE * 4.,
+            },
+        );
+        let index = TriggerIndex::new(vec![huge]);
+        assert_eq!(index.large, [0]);
+        assert!(index.cells.is_empty());
+        assert_eq!(index.containing(vec3(50., 0., 0.)).count(), 1);
+    }
+}
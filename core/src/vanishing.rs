@@ -0,0 +1,239 @@
+use ambient_api::{
+    core::{
+        physics::components::cube_collider,
+        rendering::components::{color, transparency_group},
+        transform::components::{rotation, translation},
+    },
+    prelude::*,
+};
+
+use crate::{
+    packages::{
+        tangent_rider_schema::{
+            components::{
+                crumble_cooldown, crumble_delay, game_phase, is_spawned, placed_position,
+                placed_rotation, spawned_from, timed_gone_time, timed_offset, timed_solid_time,
+            },
+            concepts::Spawnable,
+            messages::{TriggerEnter, TriggerStay},
+            types::GamePhase,
+        },
+        this::components::{crumble_touch_time, is_vanished},
+    },
+    race_elapsed,
+};
+
+/// How far below the map vanished objects are kept, in metres, so that their triggers can't be
+/// set off.
+const VANISHED_DEPTH: f32 = 100.0;
+
+/// Makes crumbling objects fall away after vehicles drive onto them, and timed objects come and
+/// go on a fixed cycle.
+///
+/// Both are driven by the race clock, so every run starts with them all in place.
+pub fn setup() {
+    // Vehicles already on a crumbling object when the race starts set it off as soon as it does
+    TriggerEnter::subscribe(|_ctx, msg| touch(msg.trigger_id));
+    TriggerStay::subscribe(|_ctx, msg| touch(msg.trigger_id));
+
+    query(placed_position())
+        .requires(is_spawned())
+        .each_frame(|objects| {
+            // During the scoreboard, the replay takes over showing them, so they all need to be
+            // visible
+            if entity::get_component(entity::synchronized_resources(), game_phase())
+                == Some(GamePhase::Scoreboard)
+            {
+                for (object_id, _) in objects {
+                    set_vanished(object_id, false);
+                }
+                return;
+            }
+
+            let time = race_elapsed().map(|elapsed| elapsed.as_secs_f32());
+            for (object_id, _) in objects {
+                if !is_vanishing(object_id) {
+                    continue;
+                }
+
+                let Some(time) = time else {
+                    // Reset everything before the race starts
+                    entity::remove_component(object_id, crumble_touch_time());
+                    set_vanished(object_id, false);
+                    continue;
+                };
+
+                let crumbled = match entity::get_component(object_id, crumble_touch_time()) {
+                    Some(touch_time) => {
+                        let delay = entity::get_component(object_id, crumble_delay());
+                        let cooldown = entity::get_component(object_id, crumble_cooldown());
+                        match crumble_stage(
+                            time - touch_time,
+                            delay.unwrap_or_default(),
+                            cooldown.unwrap_or_default(),
+                        ) {
+                            CrumbleStage::Crumbling => false,
+                            CrumbleStage::Gone => true,
+                            CrumbleStage::Back => {
+                                // Once it comes back, it can be set off again
+                                entity::remove_component(object_id, crumble_touch_time());
+                                false
+                            }
+                        }
+                    }
+                    None => false,
+                };
+                set_vanished(object_id, crumbled || is_timed_out(object_id, time));
+            }
+        });
+}
+
+/// Returns true if the object comes and goes during the race.
+pub fn is_vanishing(id: EntityId) -> bool {
+    entity::has_component(id, crumble_delay()) || entity::has_component(id, timed_solid_time())
+}
+
+fn touch(object_id: EntityId) {
+    if !entity::has_component(object_id, is_spawned())
+        || !entity::has_component(object_id, crumble_delay())
+        || entity::has_component(object_id, crumble_touch_time())
+    {
+        return;
+    }
+    let Some(elapsed) = race_elapsed() else {
+        return;
+    };
+
+    entity::add_component(object_id, crumble_touch_time(), elapsed.as_secs_f32());
+}
+
+/// Where a crumbling object is in its cycle after being touched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CrumbleStage {
+    /// It's still there, but about to crumble.
+    Crumbling,
+    Gone,
+    /// It's come back, and can be set off again.
+    Back,
+}
+
+/// Returns the stage a crumbling object is at the given time after it was first touched.
+fn crumble_stage(since_touch: f32, delay: f32, cooldown: f32) -> CrumbleStage {
+    if since_touch >= delay + cooldown {
+        CrumbleStage::Back
+    } else if since_touch >= delay {
+        CrumbleStage::Gone
+    } else {
+        CrumbleStage::Crumbling
+    }
+}
+
+/// Returns true if the timed object is in the gone part of its cycle at the given race time.
+fn is_timed_out(id: EntityId, time: f32) -> bool {
+    let Some(solid_time) = entity::get_component(id, timed_solid_time()) else {
+        return false;
+    };
+    let gone_time = entity::get_component(id, timed_gone_time()).unwrap_or_default();
+    let offset = entity::get_component(id, timed_offset()).unwrap_or_default();
+    is_in_gone_time(time + offset, solid_time, gone_time)
+}
+
+/// Returns true if the given time falls in the gone part of a cycle that is solid for
+/// `solid_time` seconds, then gone for `gone_time` seconds.
+fn is_in_gone_time(time: f32, solid_time: f32, gone_time: f32) -> bool {
+    let cycle = solid_time.max(0.) + gone_time.max(0.);
+    if cycle <= 0. {
+        return false;
+    }
+
+    time.rem_euclid(cycle) >= solid_time
+}
+
+/// Makes the object invisible and non-solid, or restores it from the spawnable it was spawned
+/// from. Vanished objects are also moved under the map, so that their triggers aren't set off,
+/// and are put back in their placed pose when they return. Animated objects then carry on from
+/// there.
+fn set_vanished(id: EntityId, vanished: bool) {
+    if vanished == entity::has_component(id, is_vanished()) {
+        return;
+    }
+    let template_id = entity::get_component(id, spawned_from())
+        .and_then(Spawnable::get_spawned)
+        .map(|spawnable| spawnable.spawnable_main_ref);
+    let (Some(template_id), Some(position), Some(rot)) = (
+        template_id,
+        entity::get_component(id, placed_position()),
+        entity::get_component(id, placed_rotation()),
+    ) else {
+        return;
+    };
+
+    if vanished {
+        entity::add_component(id, is_vanished(), ());
+        entity::remove_component(id, cube_collider());
+        let hidden_color =
+            entity::get_component(id, color()).unwrap_or(Vec4::ONE) * vec4(1., 1., 1., 0.);
+        entity::add_component(id, color(), hidden_color);
+        entity::add_component(id, transparency_group(), 0);
+        entity::set_component(id, translation(), position - Vec3::Z * VANISHED_DEPTH);
+    } else {
+        entity::remove_component(id, is_vanished());
+        match entity::get_component(template_id, cube_collider()) {
+            Some(size) => entity::add_component(id, cube_collider(), size),
+            None => entity::remove_component(id, cube_collider()),
+        }
+        match entity::get_component(template_id, color()) {
+            Some(original) => entity::add_component(id, color(), original),
+            None => entity::remove_component(id, color()),
+        }
+        match entity::get_component(template_id, transparency_group()) {
+            Some(group) => entity::add_component(id, transparency_group(), group),
+            None => entity::remove_component(id, transparency_group()),
+        }
+        entity::set_component(id, translation(), position);
+        entity::set_component(id, rotation(), rot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crumble_stages() {
+        assert_eq!(crumble_stage(0., 0.5, 3.), CrumbleStage::Crumbling);
+        assert_eq!(crumble_stage(0.49, 0.5, 3.), CrumbleStage::Crumbling);
+        assert_eq!(crumble_stage(0.5, 0.5, 3.), CrumbleStage::Gone);
+        assert_eq!(crumble_stage(3.49, 0.5, 3.), CrumbleStage::Gone);
+        assert_eq!(crumble_stage(3.5, 0.5, 3.), CrumbleStage::Back);
+    }
+
+    #[test]
+    fn crumble_without_delay_is_gone_at_once() {
+        assert_eq!(crumble_stage(0., 0., 1.), CrumbleStage::Gone);
+        assert_eq!(crumble_stage(0., 0., 0.), CrumbleStage::Back);
+    }
+
+    #[test]
+    fn timed_cycle() {
+        // Solid for 2 seconds, then gone for 1.5, repeating
+        let cycle: Vec<bool> = [0., 1.9, 2., 3.4, 3.5, 5.4, 5.5]
+            .into_iter()
+            .map(|time| is_in_gone_time(time, 2., 1.5))
+            .collect();
+        assert_eq!(cycle, [false, false, true, true, false, false, true]);
+    }
+
+    #[test]
+    fn timed_cycle_before_start() {
+        // Negative times, from offsets, wrap around into the previous cycle
+        assert!(is_in_gone_time(-0.5, 2., 1.5));
+        assert!(!is_in_gone_time(-2., 2., 1.5));
+    }
+
+    #[test]
+    fn timed_cycle_without_length_is_always_solid() {
+        assert!(!is_in_gone_time(1., 0., 0.));
+        assert!(!is_in_gone_time(1., -1., -1.));
+    }
+}
@@ -0,0 +1,302 @@
+use std::f32::consts::PI;
+
+use ambient_api::{
+    core::{
+        camera::components::fovy,
+        physics::components::linear_velocity,
+        transform::components::{lookat_target, rotation, scale, translation},
+    },
+    ecs::GeneralQuery,
+    input::{Input, InputDelta},
+    prelude::*,
+};
+
+use crate::{
+    controls::{Action, Controls},
+    packages::{
+        tangent_rider_schema::components::is_spawned, this::components::vehicle_camera_mode,
+    },
+};
+
+/// Where the chase camera sits relative to the vehicle, when it's facing forward.
+const CHASE_OFFSET: Vec3 = vec3(1.5, 5.4, 1.8);
+/// Where the bumper camera sits relative to the vehicle.
+const BUMPER_OFFSET: Vec3 = vec3(0., -1.3, 0.4);
+/// How quickly the chase camera catches up with the vehicle. Higher is stiffer.
+const CHASE_POSITION_DAMPING: f32 = 8.0;
+const CHASE_ROTATION_DAMPING: f32 = 5.0;
+/// How far the camera stays in front of anything blocking its view of the vehicle.
+const LINE_OF_SIGHT_PADDING: f32 = 0.3;
+/// The distance of the orbit camera from the vehicle.
+const ORBIT_DISTANCE: f32 = 8.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraMode {
+    Chase,
+    Bumper,
+    Orbit,
+}
+impl CameraMode {
+    const ALL: [CameraMode; 3] = [CameraMode::Chase, CameraMode::Bumper, CameraMode::Orbit];
+
+    /// Returns the mode the player has chosen, which persists between rounds.
+    pub fn current() -> Self {
+        entity::get_component(entity::resources(), vehicle_camera_mode())
+            .and_then(|index| Self::ALL.get(index as usize).copied())
+            .unwrap_or(CameraMode::Chase)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraMode::Chase => "Chase",
+            CameraMode::Bumper => "Bumper",
+            CameraMode::Orbit => "Orbit",
+        }
+    }
+
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Follows a vehicle using the player's chosen camera mode.
+pub struct VehicleCamera {
+    /// The smoothed position and rotation of the chase camera, if it has started following.
+    chase: Option<(Vec3, Quat)>,
+    orbit_yaw: f32,
+    orbit_pitch: f32,
+    /// The placed objects that can block the camera's view of the vehicle.
+    obstacles: GeneralQuery<(Component<Vec3>, Component<Quat>, Component<Vec3>)>,
+}
+impl Default for VehicleCamera {
+    fn default() -> Self {
+        Self {
+            chase: None,
+            orbit_yaw: 0.,
+            orbit_pitch: 0.,
+            obstacles: query((translation(), rotation(), scale()))
+                .requires(is_spawned())
+                .build(),
+        }
+    }
+}
+impl VehicleCamera {
+    pub fn tick(
+        &mut self,
+        camera_id: EntityId,
+        vehicle_id: EntityId,
+        delta: &InputDelta,
+        input: &Input,
+    ) -> Option<()> {
+        let controls = Controls::get();
+        let mut mode = CameraMode::current();
+        if controls.was_released(Action::SwitchCamera, delta) {
+            mode = mode.next();
+            let index = CameraMode::ALL.iter().position(|m| *m == mode).unwrap_or(0);
+            entity::add_component(entity::resources(), vehicle_camera_mode(), index as u32);
+        }
+
+        let position = entity::get_component(vehicle_id, translation())?;
+        let vehicle_rotation = entity::get_component(vehicle_id, rotation())?;
+        let speed = entity::get_component(vehicle_id, linear_velocity())?.length();
+
+        let (eye, forward) = match mode {
+            CameraMode::Chase => self.chase(position, vehicle_rotation),
+            CameraMode::Bumper => {
+                self.chase = None;
+                (
+                    position + vehicle_rotation * BUMPER_OFFSET,
+                    vehicle_rotation * -Vec3::Y,
+                )
+            }
+            CameraMode::Orbit => {
+                self.chase = None;
+                if controls.is_down(Action::Look, input) {
+                    let look = controls.look_delta(delta.mouse_position);
+                    self.orbit_yaw = (self.orbit_yaw + look.x).rem_euclid(2. * PI);
+                    self.orbit_pitch =
+                        (self.orbit_pitch + look.y).clamp(-80f32.to_radians(), 80f32.to_radians());
+                }
+                let orbit_rotation =
+                    Quat::from_rotation_z(self.orbit_yaw) * Quat::from_rotation_x(self.orbit_pitch);
+                let eye = self.pull_in(
+                    position,
+                    position + orbit_rotation * Vec3::Y * ORBIT_DISTANCE,
+                );
+                (eye, (position - eye).normalize_or_zero())
+            }
+        };
+
+        entity::set_component(camera_id, translation(), eye);
+        entity::set_component(camera_id, lookat_target(), eye + forward);
+        entity::set_component(
+            camera_id,
+            fovy(),
+            0.9 + (speed.abs() / 300.0).clamp(0.0, 1.0),
+        );
+
+        Some(())
+    }
+
+    /// Follows behind the vehicle, easing towards its position and heading so that flips and
+    /// spins don't throw the camera around. Only the vehicle's heading is followed, so the
+    /// camera stays upright.
+    fn chase(&mut self, position: Vec3, vehicle_rotation: Quat) -> (Vec3, Vec3) {
+        let forward = (vehicle_rotation * -Vec3::Y).truncate();
+        let heading = if forward.length_squared() > 0.01 {
+            Quat::from_rotation_z(forward.y.atan2(forward.x) + PI / 2.)
+        } else {
+            // The vehicle is pointing straight up or down, so keep the last heading
+            self.chase.map_or(Quat::IDENTITY, |(_, heading)| heading)
+        };
+
+        let target = position + heading * CHASE_OFFSET;
+        let (eye, heading) = match self.chase {
+            Some((eye, last_heading)) => {
+                let dt = delta_time();
+                (
+                    eye.lerp(target, 1. - (-CHASE_POSITION_DAMPING * dt).exp()),
+                    last_heading.slerp(heading, 1. - (-CHASE_ROTATION_DAMPING * dt).exp()),
+                )
+            }
+            None => (target, heading),
+        };
+        self.chase = Some((eye, heading));
+
+        (self.pull_in(position, eye), heading * -Vec3::Y)
+    }
+
+    /// Moves the camera towards the vehicle if a placed object is between them, so that the
+    /// vehicle is never hidden.
+    fn pull_in(&self, vehicle_position: Vec3, eye: Vec3) -> Vec3 {
+        let origin = vehicle_position + Vec3::Z * 0.5;
+        let to_eye = eye - origin;
+        let distance = to_eye.length();
+        if distance < 0.001 {
+            return eye;
+        }
+        let direction = to_eye / distance;
+
+        let hit = self
+            .obstacles
+            .evaluate()
+            .into_iter()
+            .filter_map(|(_, (position, rot, size))| {
+                ray_box_distance(origin, direction, position, rot, size / 2.)
+            })
+            .filter(|hit_distance| *hit_distance < distance)
+            .min_by(|a, b| a.total_cmp(b));
+
+        match hit {
+            Some(hit_distance) => {
+                origin + direction * (hit_distance - LINE_OF_SIGHT_PADDING).max(0.)
+            }
+            None => eye,
+        }
+    }
+}
+
+/// Returns the distance along the ray to where it enters the oriented box, if it hits it.
+fn ray_box_distance(
+    origin: Vec3,
+    direction: Vec3,
+    center: Vec3,
+    rot: Quat,
+    half_extents: Vec3,
+) -> Option<f32> {
+    // Work in the box's space, where it's axis-aligned
+    let inverse = rot.inverse();
+    let origin = inverse * (origin - center);
+    let direction = inverse * direction;
+
+    let mut near = f32::NEG_INFINITY;
+    let mut far = f32::INFINITY;
+    for axis in 0..3 {
+        if direction[axis].abs() < 1e-6 {
+            if origin[axis].abs() > half_extents[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let a = (-half_extents[axis] - origin[axis]) / direction[axis];
+        let b = (half_extents[axis] - origin[axis]) / direction[axis];
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+
+    // Boxes that the ray starts inside, like the one the vehicle is driving on, are ignored
+    (near <= far && near >= 0.).then_some(near)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_hits_box_in_front() {
+        let distance = ray_box_distance(
+            Vec3::ZERO,
+            Vec3::X,
+            vec3(10., 0., 0.),
+            Quat::IDENTITY,
+            Vec3::splat(1.),
+        );
+        assert_eq!(distance, Some(9.));
+    }
+
+    #[test]
+    fn ray_misses_box_behind_or_beside() {
+        let half_extents = Vec3::splat(1.);
+        let behind = ray_box_distance(
+            Vec3::ZERO,
+            Vec3::X,
+            vec3(-10., 0., 0.),
+            Quat::IDENTITY,
+            half_extents,
+        );
+        let beside = ray_box_distance(
+            Vec3::ZERO,
+            Vec3::X,
+            vec3(10., 3., 0.),
+            Quat::IDENTITY,
+            half_extents,
+        );
+        assert_eq!(behind, None);
+        assert_eq!(beside, None);
+    }
+
+    #[test]
+    fn ray_ignores_box_it_starts_inside() {
+        let distance = ray_box_distance(
+            Vec3::ZERO,
+            Vec3::Z,
+            Vec3::ZERO,
+            Quat::IDENTITY,
+            vec3(5., 5., 0.5),
+        );
+        assert_eq!(distance, None);
+    }
+
+    #[test]
+    fn ray_hits_rotated_box() {
+        // A long thin box, turned to lie along the ray
+        let rot = Quat::from_rotation_z(PI / 2.);
+        let half_extents = vec3(4., 0.5, 0.5);
+        let distance =
+            ray_box_distance(vec3(0., -10., 0.), Vec3::Y, Vec3::ZERO, rot, half_extents).unwrap();
+        assert!((distance - 6.).abs() < 1e-4, "{distance}");
+
+        // Unrotated, it lies across the ray, so the ray reaches it later
+        let distance = ray_box_distance(
+            vec3(0., -10., 0.),
+            Vec3::Y,
+            Vec3::ZERO,
+            Quat::IDENTITY,
+            half_extents,
+        )
+        .unwrap();
+        assert!((distance - 9.5).abs() < 1e-4, "{distance}");
+    }
+}
@@ -3,16 +3,24 @@ use ambient_api::{
         app::components::{game_time, main_scene},
         rect::components::{line_from, line_to, line_width},
         rendering::components::{color, double_sided},
-        transform::components::local_to_world,
+        transform::components::{rotation, scale, translation},
     },
     element::{use_entity_component, use_query},
     prelude::*,
 };
-use packages::this::components::is_boost_pad;
+use packages::{
+    tangent_rider_schema::components::{player_current_spawnable_ghost, server_time, trigger_box},
+    tangent_schema::player::components::vehicle_ref,
+    this::components::{
+        boost_cooldown, boost_direction, is_boost_pad, is_launcher, last_boost_pad,
+        last_boost_time, launch_strength,
+    },
+};
 
 #[main]
 pub fn main() {
     BoostPads.el().spawn_interactive();
+    LauncherTrajectories.el().spawn_interactive();
 }
 
 #[element_component]
@@ -23,40 +31,119 @@ fn BoostPads(hooks: &mut Hooks) -> Element {
 
 #[element_component]
 fn BoostPad(hooks: &mut Hooks, id: EntityId) -> Element {
-    let ltw = use_entity_component(hooks, id, local_to_world()).unwrap_or_default();
+    let position = use_entity_component(hooks, id, translation()).unwrap_or_default();
+    let rot = use_entity_component(hooks, id, rotation()).unwrap_or_default();
+    let size = use_entity_component(hooks, id, scale()).unwrap_or(Vec3::ONE);
+    let trigger_size = use_entity_component(hooks, id, trigger_box()).unwrap_or(size);
+    let direction = use_entity_component(hooks, id, boost_direction()).unwrap_or(-Vec3::Y);
     let time = use_entity_component(hooks, entity::resources(), game_time()).unwrap_or_default();
     let t = time.as_secs_f32() / 2.0;
 
-    let height = 1.0;
+    // Chevrons point the way the pad pushes, and span its trigger, in metres in the pad's space
+    let forward = direction.truncate().try_normalize().unwrap_or(-Vec2::Y);
+    let side = forward.perp();
+    let half_size = trigger_size.truncate() / 2.0;
+    let reach = forward.abs().dot(half_size);
+    let width = side.abs().dot(half_size);
+    let height = size.z;
 
-    fn make_chevron(ltw: Mat4, t: f32, height: f32) -> Element {
+    // The pad won't boost the local vehicle again until its cooldown is over, so grey it out
+    let line_color = if is_cooling_down(id) {
+        vec4(0.3, 0.3, 0.3, 1.0)
+    } else {
+        vec4(0.8, 0.3, 0.0, 1.0)
+    };
+    let to_world = |point: Vec2| position + rot * point.extend(height);
+    let make_chevron = |t: f32| {
+        let tip = forward * t * 2.0 * reach;
+        let back = tip - forward * reach;
         Group::el([
-            make_line(
-                ltw.transform_point3(vec3(-0.5, 0.5 - t, height)),
-                ltw.transform_point3(vec3(0.0, 0.0 - t, height)),
-            ),
-            make_line(
-                ltw.transform_point3(vec3(0.0, 0.0 - t, height)),
-                ltw.transform_point3(vec3(0.5, 0.5 - t, height)),
-            ),
+            make_line(to_world(back - side * width), to_world(tip), line_color),
+            make_line(to_world(tip), to_world(back + side * width), line_color),
         ])
-    }
+    };
 
     let time_limit = 0.75;
 
     Group::el([
-        make_chevron(ltw, t % time_limit, height),
-        make_chevron(ltw, (t + 0.25) % time_limit, height),
-        make_chevron(ltw, (t + 0.5) % time_limit, height),
+        make_chevron(t % time_limit),
+        make_chevron((t + 0.25) % time_limit),
+        make_chevron((t + 0.5) % time_limit),
     ])
 }
 
-fn make_line(p0: Vec3, p1: Vec3) -> Element {
+/// Returns true if the pad boosted the local player's vehicle too recently to boost it again.
+///
+/// Boost times are stamped by the server, so this compares them against the server's clock.
+fn is_cooling_down(pad_id: EntityId) -> bool {
+    let Some(vehicle_id) = entity::get_component(player::get_local(), vehicle_ref()) else {
+        return false;
+    };
+    if entity::get_component(vehicle_id, last_boost_pad()) != Some(pad_id) {
+        return false;
+    }
+
+    let Some(now) = entity::get_component(entity::synchronized_resources(), server_time()) else {
+        return false;
+    };
+    let cooldown = entity::get_component(pad_id, boost_cooldown()).unwrap_or_default();
+    entity::get_component(vehicle_id, last_boost_time()).is_some_and(|boost_time| {
+        now.checked_sub(boost_time)
+            .map_or(true, |elapsed| elapsed.as_secs_f32() < cooldown)
+    })
+}
+
+/// How far ahead launcher trajectories are predicted, in seconds.
+const TRAJECTORY_DURATION: f32 = 4.0;
+/// The time between points on a predicted trajectory, in seconds.
+const TRAJECTORY_STEP: f32 = 0.1;
+/// The acceleration due to gravity, which trajectories are predicted with.
+const GRAVITY: Vec3 = vec3(0.0, 0.0, -9.81);
+
+/// Draws where each launcher being placed will throw vehicles, so that builders can aim it.
+///
+/// The trajectory is for a vehicle that drives onto the launcher slowly. Vehicles keep the speed
+/// they arrive with, so faster ones are thrown further along their way.
+#[element_component]
+fn LauncherTrajectories(hooks: &mut Hooks) -> Element {
+    let ghosts = use_query(hooks, player_current_spawnable_ghost());
+    Group::el(
+        ghosts
+            .into_iter()
+            .filter(|(_, ghost_id)| entity::has_component(*ghost_id, is_launcher()))
+            .map(|(_, ghost_id)| LauncherTrajectory::el(ghost_id)),
+    )
+}
+
+#[element_component]
+fn LauncherTrajectory(hooks: &mut Hooks, id: EntityId) -> Element {
+    let position = use_entity_component(hooks, id, translation()).unwrap_or_default();
+    let rot = use_entity_component(hooks, id, rotation()).unwrap_or_default();
+    let strength = use_entity_component(hooks, id, launch_strength()).unwrap_or_default();
+
+    // Follows the launch until it hits the water
+    let velocity = rot * Vec3::Z * strength;
+    let points: Vec<Vec3> = (0..=(TRAJECTORY_DURATION / TRAJECTORY_STEP) as usize)
+        .map(|i| {
+            let t = i as f32 * TRAJECTORY_STEP;
+            position + velocity * t + GRAVITY * t * t / 2.0
+        })
+        .take_while(|point| point.z > 0.0)
+        .collect();
+
+    Group::el(
+        points
+            .windows(2)
+            .map(|segment| make_line(segment[0], segment[1], vec4(0.2, 0.6, 1.0, 1.0))),
+    )
+}
+
+fn make_line(p0: Vec3, p1: Vec3, line_color: Vec4) -> Element {
     Element::new()
         .with(main_scene(), ())
         .with(line_from(), p0)
         .with(line_to(), p1)
         .with(line_width(), 0.2)
-        .with(color(), vec4(0.8, 0.3, 0.0, 1.0))
+        .with(color(), line_color)
         .with(double_sided(), true)
 }
@@ -1,6 +1,6 @@
 use ambient_api::{
     core::{
-        physics::components::cube_collider,
+        physics::components::{cube_collider, linear_velocity, mass},
         primitives::components::cube,
         rendering::components::color,
         transform::components::{rotation, scale, translation},
@@ -8,9 +8,22 @@ use ambient_api::{
     prelude::*,
 };
 use packages::{
-    tangent_rider_schema::{components::autospinner, concepts::Spawnable},
-    tangent_schema::vehicle::components::is_vehicle,
-    this::components::{is_boost_pad, last_boost_time},
+    tangent_rider_schema::{
+        components::{
+            autospinner, crumble_cooldown, crumble_delay, is_spawned, linked_objects, mover_easing,
+            mover_pause, mover_speed, mover_waypoints, oscillator_angle, oscillator_period,
+            oscillator_pivot, spawnable_part_ghost_refs, spawnable_part_main_refs,
+            surface_material, timed_gone_time, timed_solid_time, trigger_box,
+        },
+        concepts::Spawnable,
+        messages::{TriggerEnter, TriggerStay},
+        types::{MoverEasing, SurfaceMaterial},
+    },
+    this::components::{
+        boost_cooldown, boost_direction, boost_strength, is_boost_pad, is_launcher,
+        is_teleporter_entrance, is_teleporter_exit, last_boost_pad, last_boost_time,
+        launch_strength,
+    },
 };
 
 #[main]
@@ -90,7 +103,147 @@ pub fn main() {
         .spawn();
     }
 
+    {
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), vec3(5.0, 10.0, 0.2))
+            .with(mover_waypoints(), vec![vec3(10.0, 0.0, 0.0)])
+            .with(mover_speed(), 4.0)
+            .with(mover_pause(), 1.0)
+            .with(mover_easing(), MoverEasing::EaseInOut);
+
+        Spawnable {
+            spawnable_name: "Moving Plank".to_string(),
+            spawnable_cost: 200,
+            spawnable_main_ref: base.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: base.spawn(),
+        }
+        .spawn();
+    }
+
+    {
+        // Hangs from its top end, and swings from side to side
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), vec3(1.5, 1.5, 8.0))
+            .with(oscillator_angle(), vec3(0.0, 0.0, 60f32.to_radians()))
+            .with(oscillator_period(), 3.0)
+            .with(oscillator_pivot(), vec3(0.0, 0.0, 4.0));
+
+        Spawnable {
+            spawnable_name: "Pendulum".to_string(),
+            spawnable_cost: 250,
+            spawnable_main_ref: base.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: base.spawn(),
+        }
+        .spawn();
+    }
+    {
+        // Sweeps back and forth across the ground around one end, like a gate
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), vec3(0.5, 12.0, 1.0))
+            .with(oscillator_angle(), vec3(75f32.to_radians(), 0.0, 0.0))
+            .with(oscillator_period(), 4.0)
+            .with(oscillator_pivot(), vec3(0.0, 6.0, 0.0));
+
+        Spawnable {
+            spawnable_name: "Sweeper".to_string(),
+            spawnable_cost: 200,
+            spawnable_main_ref: base.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: base.spawn(),
+        }
+        .spawn();
+    }
+
+    surfaces();
+    vanishing_planks();
     boost_pads();
+    launchers();
+    teleporters();
+}
+
+fn surfaces() {
+    for (name, cost, size, surface_color, material) in [
+        (
+            "Ice Plank",
+            100,
+            vec3(5.0, 10.0, 0.2),
+            vec4(0.7, 0.9, 1.0, 1.0),
+            SurfaceMaterial::Ice,
+        ),
+        (
+            "Trampoline",
+            200,
+            vec3(5.0, 5.0, 0.4),
+            vec4(1.0, 0.2, 0.7, 1.0),
+            SurfaceMaterial::Bouncy,
+        ),
+        (
+            "Mud",
+            75,
+            vec3(5.0, 10.0, 0.2),
+            vec4(0.35, 0.22, 0.1, 1.0),
+            SurfaceMaterial::Sticky,
+        ),
+    ] {
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), size)
+            .with(color(), surface_color)
+            .with(surface_material(), material);
+
+        Spawnable {
+            spawnable_name: name.to_string(),
+            spawnable_cost: cost,
+            spawnable_main_ref: base.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: base.spawn(),
+        }
+        .spawn();
+    }
+}
+
+fn vanishing_planks() {
+    {
+        // Gives way shortly after being driven onto, and comes back a little later
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), vec3(5.0, 10.0, 0.2))
+            .with(color(), vec4(0.6, 0.45, 0.3, 1.0))
+            .with(crumble_delay(), 0.5)
+            .with(crumble_cooldown(), 3.0);
+
+        Spawnable {
+            spawnable_name: "Crumbling Plank".to_string(),
+            spawnable_cost: 125,
+            spawnable_main_ref: base.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: base.spawn(),
+        }
+        .spawn();
+    }
+    {
+        // Comes and goes every few seconds, whether or not anyone is on it
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), vec3(5.0, 10.0, 0.2))
+            .with(color(), vec4(0.3, 0.8, 0.4, 1.0))
+            .with(timed_solid_time(), 2.0)
+            .with(timed_gone_time(), 1.5);
+
+        Spawnable {
+            spawnable_name: "Timed Plank".to_string(),
+            spawnable_cost: 150,
+            spawnable_main_ref: base.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: base.spawn(),
+        }
+        .spawn();
+    }
 }
 
 fn boost_pads() {
@@ -101,7 +254,10 @@ fn boost_pads() {
             .with(scale(), vec3(5.0, 7.5, 0.2))
             .with(color(), vec4(0.1, 0.1, 0.1, 1.0))
             .with(is_boost_pad(), ())
-            .with(last_boost_time(), game_time());
+            .with(boost_strength(), 20000.0)
+            .with(boost_direction(), -Vec3::Y)
+            .with(boost_cooldown(), 1.0)
+            .with(trigger_box(), vec3(5.0, 7.5, 4.0));
 
         Spawnable {
             spawnable_name: "Boost Pad".to_string(),
@@ -112,27 +268,150 @@ fn boost_pads() {
         .spawn();
     }
 
-    // Handle touching boost pads.
-    let boost_pad_query = query((translation(), rotation(), last_boost_time()))
-        .requires(is_boost_pad())
-        .build();
-    query(translation())
-        .requires(is_vehicle())
-        .each_frame(move |vehicles| {
-            let boost_pad = boost_pad_query.evaluate();
-
-            for (vehicle_id, position) in vehicles {
-                if let Some((boost_id, (_, boost_rotation, _))) = boost_pad
-                    .iter()
-                    .find(|(_, (boost_position, _, last_boost_time))| {
-                        boost_position.distance_squared(position) < 6.0f32.powi(2)
-                            && (game_time() - *last_boost_time).as_secs_f32() > 1.0
-                    })
-                    .copied()
-                {
-                    physics::add_force(vehicle_id, boost_rotation * -Vec3::Y * 20000.0);
-                    entity::add_component(boost_id, last_boost_time(), game_time());
-                }
-            }
-        });
+    // Handle touching boost pads. Vehicles sitting on a pad are boosted again once it cools down.
+    TriggerEnter::subscribe(|_ctx, msg| boost(msg.trigger_id, msg.vehicle_id));
+    TriggerStay::subscribe(|_ctx, msg| boost(msg.trigger_id, msg.vehicle_id));
+}
+
+fn boost(pad_id: EntityId, vehicle_id: EntityId) {
+    if !entity::has_component(pad_id, is_boost_pad())
+        || !entity::has_component(pad_id, is_spawned())
+        || is_cooling_down(vehicle_id, pad_id)
+    {
+        return;
+    }
+
+    let rot = entity::get_component(pad_id, rotation()).unwrap_or_default();
+    let strength = entity::get_component(pad_id, boost_strength()).unwrap_or_default();
+    let direction = entity::get_component(pad_id, boost_direction())
+        .unwrap_or(-Vec3::Y)
+        .normalize_or_zero();
+    physics::add_force(vehicle_id, rot * direction * strength);
+    entity::add_component(vehicle_id, last_boost_time(), game_time());
+    entity::add_component(vehicle_id, last_boost_pad(), pad_id);
+}
+
+/// Returns true if the pad boosted the vehicle too recently to boost it again. Each vehicle has
+/// its own cooldown, so that vehicles crossing a pad together are all boosted.
+fn is_cooling_down(vehicle_id: EntityId, pad_id: EntityId) -> bool {
+    if entity::get_component(vehicle_id, last_boost_pad()) != Some(pad_id) {
+        return false;
+    }
+
+    let cooldown = entity::get_component(pad_id, boost_cooldown()).unwrap_or_default();
+    entity::get_component(vehicle_id, last_boost_time())
+        .is_some_and(|time| (game_time() - time).as_secs_f32() < cooldown)
+}
+
+fn launchers() {
+    {
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), vec3(5.0, 5.0, 0.4))
+            .with(color(), vec4(0.2, 0.6, 1.0, 1.0))
+            .with(is_launcher(), ())
+            .with(launch_strength(), 18.0)
+            .with(trigger_box(), vec3(5.0, 5.0, 3.0));
+
+        Spawnable {
+            spawnable_name: "Launcher".to_string(),
+            spawnable_cost: 200,
+            spawnable_main_ref: base.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: base.spawn(),
+        }
+        .spawn();
+    }
+
+    // Handle driving onto launchers.
+    TriggerEnter::subscribe(|_ctx, msg| {
+        let launcher_id = msg.trigger_id;
+        if !entity::has_component(launcher_id, is_launcher())
+            || !entity::has_component(launcher_id, is_spawned())
+        {
+            return;
+        }
+        let Some(vehicle_mass) = entity::get_component(msg.vehicle_id, mass()) else {
+            return;
+        };
+
+        // The impulse is scaled by mass, so that every vehicle gets the same kick
+        let up = entity::get_component(launcher_id, rotation()).unwrap_or_default() * Vec3::Z;
+        let strength = entity::get_component(launcher_id, launch_strength()).unwrap_or_default();
+        physics::add_impulse(msg.vehicle_id, up * strength * vehicle_mass);
+    });
+}
+
+fn teleporters() {
+    {
+        let base = Entity::new()
+            .with(cube(), ())
+            .with(translation(), Vec3::Z * -100.)
+            .with(scale(), vec3(5.0, 5.0, 0.2));
+        let entrance = base
+            .clone()
+            .with(color(), vec4(0.6, 0.2, 1.0, 1.0))
+            .with(is_teleporter_entrance(), ())
+            .with(trigger_box(), vec3(5.0, 5.0, 3.0));
+        let exit = base
+            .with(color(), vec4(1.0, 0.6, 0.2, 1.0))
+            .with(is_teleporter_exit(), ());
+
+        // The entrance is placed first, then the exit
+        Spawnable {
+            spawnable_name: "Teleporter".to_string(),
+            spawnable_cost: 300,
+            spawnable_main_ref: entrance.clone().with(cube_collider(), Vec3::ONE).spawn(),
+            spawnable_ghost_ref: entrance.spawn(),
+        }
+        .make()
+        .with(
+            spawnable_part_main_refs(),
+            vec![exit.clone().with(cube_collider(), Vec3::ONE).spawn()],
+        )
+        .with(spawnable_part_ghost_refs(), vec![exit.spawn()])
+        .spawn();
+    }
+
+    // Handle driving into teleporters.
+    TriggerEnter::subscribe(|_ctx, msg| {
+        let entrance_id = msg.trigger_id;
+        if !entity::has_component(entrance_id, is_teleporter_entrance())
+            || !entity::has_component(entrance_id, is_spawned())
+        {
+            return;
+        }
+        let Some(exit_id) = entity::get_component(entrance_id, linked_objects())
+            .unwrap_or_default()
+            .into_iter()
+            .find(|id| entity::has_component(*id, is_teleporter_exit()))
+        else {
+            return;
+        };
+
+        let vehicle_id = msg.vehicle_id;
+        let (Some(position), Some(vehicle_rotation), Some(velocity), Some(vehicle_mass)) = (
+            entity::get_component(vehicle_id, translation()),
+            entity::get_component(vehicle_id, rotation()),
+            entity::get_component(vehicle_id, linear_velocity()),
+            entity::get_component(vehicle_id, mass()),
+        ) else {
+            return;
+        };
+        let entrance_position =
+            entity::get_component(entrance_id, translation()).unwrap_or_default();
+        let entrance_rotation = entity::get_component(entrance_id, rotation()).unwrap_or_default();
+        let exit_position = entity::get_component(exit_id, translation()).unwrap_or_default();
+        let exit_rotation = entity::get_component(exit_id, rotation()).unwrap_or_default();
+
+        // Come out of the exit just as the vehicle went into the entrance, relative to each
+        let turn = exit_rotation * entrance_rotation.inverse();
+        entity::set_component(
+            vehicle_id,
+            translation(),
+            exit_position + turn * (position - entrance_position),
+        );
+        entity::set_component(vehicle_id, rotation(), turn * vehicle_rotation);
+        physics::add_impulse(vehicle_id, (turn * velocity - velocity) * vehicle_mass);
+    });
 }